@@ -1,8 +1,9 @@
 // Tauri commands for crypto operations
 
-use crate::models::{EncryptionResult, DecryptionResult, OperationResult};
-use tauri::State;
-use crate::core::storage::Vault;
+use crate::models::{EncryptionResult, DecryptionResult, OperationResult, SignatureInfo, UserIdValidity, Validity};
+use tauri::AppHandle;
+use crate::core::{storage::Vault, trust};
+use super::operations::{copy_unless_cancelled, run_operation, OperationContext};
 
 #[tauri::command]
 pub async fn encrypt_file_cmd(
@@ -10,7 +11,24 @@ pub async fn encrypt_file_cmd(
     output_path: String,
     recipient_fingerprints: Vec<String>,
     armor: bool,
-    vault: State<'_, Vault>,
+    operation_id: Option<String>,
+    app: AppHandle,
+) -> Result<OperationResult<EncryptionResult>, String> {
+    run_operation(app, operation_id, "encrypt_file", move |vault, ctx| {
+        ctx.progress(10, "Encrypting file");
+        encrypt_file(&input_path, &output_path, recipient_fingerprints, armor, vault, ctx)
+            .unwrap_or_else(OperationResult::err)
+    })
+    .await
+}
+
+fn encrypt_file(
+    input_path: &str,
+    output_path: &str,
+    recipient_fingerprints: Vec<String>,
+    armor: bool,
+    vault: &Vault,
+    ctx: &OperationContext,
 ) -> Result<OperationResult<EncryptionResult>, String> {
     use std::fs::File;
     use sequoia_openpgp as openpgp;
@@ -65,56 +83,77 @@ pub async fn encrypt_file_cmd(
     }
 
     // 2. Prepare Output Stream
-    let output_file = File::create(&output_path).map_err(|e| e.to_string())?;
-    
-    let message = Message::new(output_file);
-    
-    // Ownership Model: Each layer consumes the previous one.
-    // Message -> Armorer -> Encryptor -> LiteralWriter
-    if armor {
-        let armor_writer = Armorer::new(message)
-            .kind(openpgp::armor::Kind::Message)
-            .build()
-            .map_err(|e| e.to_string())?;
-        
-        #[allow(deprecated)]
-        let encryptor = Encryptor::for_recipients(armor_writer, recipients_keys)
-            .build()
-            .map_err(|e| e.to_string())?;
-
-        // 3. Write Literal Data
-        // LiteralWriter takes ownership of encryptor
-        let mut writer = LiteralWriter::new(encryptor).build().map_err(|e| e.to_string())?;
-        
-        let mut input_file = File::open(&input_path).map_err(|e| e.to_string())?;
-        std::io::copy(&mut input_file, &mut writer).map_err(|e| e.to_string())?;
-        
-        // 4. Finalize chain (Unwinding)
-        // We only finalize the top-level writer. 
-        // The inner writers (Encryptor, Armorer) will be finalized on Drop.
-        // This is necessary because LiteralWriter::finalize() returns () in this configuration.
-        writer.finalize().map_err(|e| e.to_string())?;
-
-    } else {
-        // Message -> Encryptor -> LiteralWriter
-        #[allow(deprecated)]
-        let encryptor = Encryptor::for_recipients(message, recipients_keys)
-            .build()
-            .map_err(|e| e.to_string())?;
-
-        // 3. Write Literal Data
-        let mut writer = LiteralWriter::new(encryptor).build().map_err(|e| e.to_string())?;
-        
-        let mut input_file = File::open(&input_path).map_err(|e| e.to_string())?;
-        std::io::copy(&mut input_file, &mut writer).map_err(|e| e.to_string())?;
-        
-        // 4. Finalize chain
-        writer.finalize().map_err(|e| e.to_string())?;
+    let output_file = File::create(output_path).map_err(|e| e.to_string())?;
+
+    // Returns false if cancelled; a cancelled or failed run leaves no
+    // partial output behind.
+    let write = move || -> Result<bool, String> {
+        let message = Message::new(output_file);
+
+        // Ownership Model: Each layer consumes the previous one.
+        // Message -> Armorer -> Encryptor -> LiteralWriter
+        if armor {
+            let armor_writer = Armorer::new(message)
+                .kind(openpgp::armor::Kind::Message)
+                .build()
+                .map_err(|e| e.to_string())?;
+
+            #[allow(deprecated)]
+            let encryptor = Encryptor::for_recipients(armor_writer, recipients_keys)
+                .build()
+                .map_err(|e| e.to_string())?;
+
+            // 3. Write Literal Data
+            // LiteralWriter takes ownership of encryptor
+            let mut writer = LiteralWriter::new(encryptor).build().map_err(|e| e.to_string())?;
+
+            let mut input_file = File::open(input_path).map_err(|e| e.to_string())?;
+            if !copy_unless_cancelled(ctx, &mut input_file, &mut writer).map_err(|e| e.to_string())? {
+                return Ok(false);
+            }
+
+            // 4. Finalize chain (Unwinding)
+            // We only finalize the top-level writer. 
+            // The inner writers (Encryptor, Armorer) will be finalized on Drop.
+            // This is necessary because LiteralWriter::finalize() returns () in this configuration.
+            writer.finalize().map_err(|e| e.to_string())?;
+
+        } else {
+            // Message -> Encryptor -> LiteralWriter
+            #[allow(deprecated)]
+            let encryptor = Encryptor::for_recipients(message, recipients_keys)
+                .build()
+                .map_err(|e| e.to_string())?;
+
+            // 3. Write Literal Data
+            let mut writer = LiteralWriter::new(encryptor).build().map_err(|e| e.to_string())?;
+
+            let mut input_file = File::open(input_path).map_err(|e| e.to_string())?;
+            if !copy_unless_cancelled(ctx, &mut input_file, &mut writer).map_err(|e| e.to_string())? {
+                return Ok(false);
+            }
+
+            // 4. Finalize chain
+            writer.finalize().map_err(|e| e.to_string())?;
+        }
+        // message does not need finalize
+        Ok(true)
+    };
+
+    match write() {
+        Ok(true) => {}
+        Ok(false) => {
+            let _ = std::fs::remove_file(output_path);
+            return Ok(ctx.cancelled());
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(output_path);
+            return Err(e);
+        }
     }
-    // message does not need finalize
 
     Ok(OperationResult::ok(EncryptionResult {
-        output_path: output_path, // Updated field name
+        output_path: output_path.to_string(), // Updated field name
         success: true,            // Added field
         size: 0, 
         recipients: recipient_fingerprints,
@@ -128,11 +167,32 @@ pub async fn decrypt_file_cmd(
     output_path: String,
     passphrase: String,
     target_fingerprint: Option<String>,
-    vault: State<'_, Vault>,
+    operation_id: Option<String>,
+    app: AppHandle,
+) -> Result<OperationResult<DecryptionResult>, String> {
+    run_operation(app, operation_id, "decrypt_file", move |vault, ctx| {
+        ctx.progress(10, "Decrypting file");
+        decrypt_file(&input_path, &output_path, passphrase, target_fingerprint, vault, ctx)
+            .unwrap_or_else(OperationResult::err)
+    })
+    .await
+}
+
+fn decrypt_file(
+    input_path: &str,
+    output_path: &str,
+    passphrase: String,
+    target_fingerprint: Option<String>,
+    vault: &Vault,
+    ctx: &OperationContext,
 ) -> Result<OperationResult<DecryptionResult>, String> {
     use std::fs::File;
     use sequoia_openpgp as openpgp;
-    use openpgp::parse::stream::{DecryptorBuilder, DecryptionHelper, VerificationHelper, MessageStructure};
+    use openpgp::parse::stream::{
+        DecryptorBuilder, DecryptionHelper, GoodChecksum, MessageLayer, MessageStructure,
+        VerificationError, VerificationHelper,
+    };
+    use std::collections::HashMap;
     use openpgp::policy::StandardPolicy;
     use openpgp::{KeyHandle, Cert};
     use openpgp::parse::Parse; // Import Parse for from_bytes/from_reader
@@ -143,13 +203,70 @@ pub async fn decrypt_file_cmd(
         conn: &'a std::sync::Mutex<rusqlite::Connection>,
         passphrase: String,
         target_fingerprint: Option<String>,
+        certs: Vec<Cert>,
+        validity: std::sync::Arc<HashMap<String, Vec<UserIdValidity>>>,
+        signatures: Vec<SignatureInfo>,
+    }
+
+    impl<'a> Helper<'a> {
+        fn signature_info(&self, sig: &openpgp::packet::Signature, cert: Option<&Cert>, valid: bool) -> SignatureInfo {
+            let fingerprint = cert
+                .map(|c| c.fingerprint().to_hex())
+                .or_else(|| sig.get_issuers().first().map(|h| h.to_hex()))
+                .unwrap_or_default();
+            let signer = cert
+                .and_then(|c| c.userids().next())
+                .map(|uid| String::from_utf8_lossy(uid.userid().value()).to_string())
+                .unwrap_or_default();
+            let created_at = sig
+                .signature_creation_time()
+                .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339())
+                .unwrap_or_default();
+            // Validity only means something for signatures that check out.
+            let validity = if valid {
+                self.validity
+                    .get(&fingerprint)
+                    .and_then(|uids| uids.iter().map(|u| u.validity).max())
+                    .unwrap_or_default()
+            } else {
+                Validity::Unknown
+            };
+
+            SignatureInfo { signer, fingerprint, created_at, valid, validity }
+        }
     }
 
     impl<'a> VerificationHelper for Helper<'a> {
-        fn get_certs(&mut self, _ids: &[KeyHandle]) -> openpgp::Result<Vec<Cert>> {
-            Ok(Vec::new()) 
+        fn get_certs(&mut self, ids: &[KeyHandle]) -> openpgp::Result<Vec<Cert>> {
+            Ok(self
+                .certs
+                .iter()
+                .filter(|cert| cert.keys().any(|k| ids.iter().any(|id| k.key().key_handle().aliases(id))))
+                .cloned()
+                .collect())
         }
-        fn check(&mut self, _structure: MessageStructure) -> openpgp::Result<()> {
+        fn check(&mut self, structure: MessageStructure) -> openpgp::Result<()> {
+            // Record every signature for the result; a bad signature does not
+            // abort decryption, it is reported as invalid instead.
+            for layer in structure.into_iter() {
+                if let MessageLayer::SignatureGroup { results } = layer {
+                    for result in results {
+                        let info = match result {
+                            Ok(GoodChecksum { sig, ka }) => self.signature_info(sig, Some(ka.cert()), true),
+                            Err(VerificationError::MalformedSignature { sig, .. })
+                            | Err(VerificationError::MissingKey { sig }) => self.signature_info(sig, None, false),
+                            Err(VerificationError::UnboundKey { sig, cert, .. }) => {
+                                self.signature_info(sig, Some(cert), false)
+                            }
+                            Err(VerificationError::BadKey { sig, ka, .. })
+                            | Err(VerificationError::BadSignature { sig, ka, .. }) => {
+                                self.signature_info(sig, Some(ka.cert()), false)
+                            }
+                        };
+                        self.signatures.push(info);
+                    }
+                }
+            }
             Ok(())
         }
     }
@@ -226,27 +343,49 @@ pub async fn decrypt_file_cmd(
         }
     }
 
+    // Certs and their computed validity, used to verify and rate signatures.
+    let certs = trust::load_vault_certs(vault).map_err(|e| e.to_string())?;
+    let validity = trust::vault_validity(vault).map_err(|e| e.to_string())?;
+
     let helper = Helper {
         conn: &vault.conn,
         passphrase,
         target_fingerprint,
+        certs,
+        validity,
+        signatures: Vec::new(),
     };
 
-    let mut input_file = File::open(&input_path).map_err(|e| e.to_string())?;
+    let mut input_file = File::open(input_path).map_err(|e| e.to_string())?;
     
     let mut decryptor = DecryptorBuilder::from_reader(&mut input_file).map_err(|e| e.to_string())?
         .with_policy(&p, None, helper)
         .map_err(|e| e.to_string())?;
 
-    let mut output_file = File::create(&output_path).map_err(|e| e.to_string())?;
-    
-    std::io::copy(&mut decryptor, &mut output_file).map_err(|e| e.to_string())?;
+    let mut output_file = File::create(output_path).map_err(|e| e.to_string())?;
+
+    // Partial plaintext is removed if the copy is cancelled or fails.
+    match copy_unless_cancelled(ctx, &mut decryptor, &mut output_file) {
+        Ok(true) => {}
+        Ok(false) => {
+            drop(output_file);
+            let _ = std::fs::remove_file(output_path);
+            return Ok(ctx.cancelled());
+        }
+        Err(e) => {
+            drop(output_file);
+            let _ = std::fs::remove_file(output_path);
+            return Err(e.to_string());
+        }
+    }
+
+    let signatures = decryptor.into_helper().signatures;
 
     Ok(OperationResult::ok(DecryptionResult {
-        output_path: output_path,
+        output_path: output_path.to_string(),
         success: true,
         size: 0,
         decrypted_with: None,
-        signatures: Vec::new(),
+        signatures,
     }))
 }
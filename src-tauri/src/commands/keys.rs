@@ -1,13 +1,21 @@
 // Tauri commands for key management
 
-use crate::core::{keyring, storage::Vault};
-use crate::models::{KeyMetadata, KeyType, OperationResult, UserId};
-use tauri::State;
+use crate::core::{audit, autocrypt, keyring, search, storage::Vault, trust, wkd};
+use crate::models::{
+    AuditReport, AutocryptSetupMessage, FingerprintAids, GnupgImportReport, ImportReport, KeyDetails, KeyExport, KeyGenProfile, KeyMetadata, KeySearchQuery, KeySearchResult, KeySheetExport, OperationResult, OwnerTrust, SubkeyProfile, UserId, VaultExport, WkdLayout, WkdReport,
+};
+use super::operations::{run_operation, OperationContext};
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub async fn list_keys(vault: State<'_, Vault>) -> Result<OperationResult<Vec<KeyMetadata>>, String> {
     match keyring::list_all_keys(&vault) {
-        Ok(keys) => Ok(OperationResult::ok(keys)),
+        Ok(mut keys) => {
+            if let Err(e) = trust::annotate_keys(&vault, &mut keys) {
+                log::error!("Failed to compute key validity: {}", e);
+            }
+            Ok(OperationResult::ok(keys))
+        }
         Err(e) => Ok(OperationResult::err(format!("Failed to list keys: {}", e))),
     }
 }
@@ -31,6 +39,7 @@ pub async fn delete_key(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn generate_key(
     name: String,
     email: String,
@@ -38,165 +47,268 @@ pub async fn generate_key(
     key_type: String,
     passphrase: String,
     expiry_timestamp: Option<i64>, // Unix timestamp in seconds
-    vault: State<'_, Vault>,
+    operation_id: Option<String>,
+    app: AppHandle,
 ) -> Result<OperationResult<KeyMetadata>, String> {
-    println!("DEBUG: generate_key called with name={}, email={}, key_type={}", name, email, key_type);
-    
-    // Calculate duration in seconds if expiry is set
-    let valid_seconds = expiry_timestamp.map(|ts| {
-        let now = chrono::Utc::now().timestamp();
-        if ts > now {
-             (ts - now) as u64
-        } else {
-             0 // Should probably error or default to minimal, but 0 often means no-expire or immediate. Sequoia behavior for 0?
-        }
-    });
+    log::debug!("generate_key called with key_type={}", key_type);
 
-    match crate::core::crypto::generate_keypair(&name, &email, &passphrase, valid_seconds, &key_type) {
-        Ok((_public_key, private_key)) => {
-            // ... (rest of function) ...
-            
-            use sequoia_openpgp as openpgp;
-            use openpgp::parse::Parse;
-            
-            let cert = match openpgp::Cert::from_bytes(private_key.as_bytes()) {
-                Ok(c) => c,
-                Err(e) => return Ok(OperationResult::err(format!("Failed to parse generated key: {}", e))),
-            };
-            
-            // Format fingerprint as hex string without spaces for filename safety
-            let fingerprint = cert.fingerprint().to_string().replace(" ", "");
-            
-            let metadata = KeyMetadata {
-                fingerprint: fingerprint.clone(),
-                key_type: if key_type == "rsa4096" {
-                    KeyType::Rsa4096
-                } else if key_type == "rsa2048" {
-                    KeyType::Rsa2048
-                } else {
-                    KeyType::Ed25519
-                },
-                user_id: UserId {
-                    name,
-                    email,
-                    comment,
-                },
-                created_at: chrono::Utc::now().to_rfc3339(),
-                expires_at: expiry_timestamp.map(|ts| {
-                    chrono::DateTime::from_timestamp(ts, 0)
-                        .map(|dt| dt.to_rfc3339())
-                        .unwrap_or_default()
-                }),
-                is_private: true,
-                is_favorite: false,
-                tags: vec![],
-            };
+    // Classic layout: certify-only primary, one signing and one encryption subkey.
+    let profile = KeyGenProfile {
+        user_ids: vec![UserId { name, email, comment }],
+        key_type,
+        expiry_timestamp,
+        certify_only_primary: true,
+        subkeys: vec![
+            SubkeyProfile { capability: "sign".to_string(), expiry_timestamp: None },
+            SubkeyProfile { capability: "encrypt_transport".to_string(), expiry_timestamp: None },
+        ],
+    };
 
-            // Save key file (private key which includes public parts)
-            if let Err(e) = keyring::save_key_to_file(&vault, &fingerprint, &private_key) {
-                return Ok(OperationResult::err(format!("Failed to save key file: {}", e)));
-            }
+    run_operation(app, operation_id, "generate_key", move |vault, ctx| {
+        generate_from_profile(vault, ctx, &profile, &passphrase)
+    })
+    .await
+}
 
-            // Save metadata
-            match keyring::save_key_metadata(&vault, &metadata) {
-                Ok(_) => Ok(OperationResult::ok(metadata)),
-                Err(e) => Ok(OperationResult::err(format!("Failed to save key metadata: {}", e))),
-            }
-        },
-        Err(e) => Ok(OperationResult::err(format!("Failed to generate key: {}", e)))
+#[tauri::command]
+pub async fn generate_key_with_profile(
+    profile: KeyGenProfile,
+    passphrase: String,
+    operation_id: Option<String>,
+    app: AppHandle,
+) -> Result<OperationResult<KeyMetadata>, String> {
+    log::info!(
+        "GENERATE_KEY_WITH_PROFILE command called with {} user IDs, {} subkeys, key_type={}",
+        profile.user_ids.len(), profile.subkeys.len(), profile.key_type
+    );
+
+    run_operation(app, operation_id, "generate_key", move |vault, ctx| {
+        generate_from_profile(vault, ctx, &profile, &passphrase)
+    })
+    .await
+}
+
+// Runs on the blocking pool; cancellation is honoured between steps, before
+// anything is written to the vault.
+fn generate_from_profile(
+    vault: &Vault,
+    ctx: &OperationContext,
+    profile: &KeyGenProfile,
+    passphrase: &str,
+) -> OperationResult<KeyMetadata> {
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    ctx.progress(10, "Generating key material");
+    let private_key = match crate::core::crypto::generate_keypair(profile, passphrase) {
+        Ok((_public_key, private_key)) => private_key,
+        Err(e) => return OperationResult::err(format!("Failed to generate key: {}", e)),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(private_key.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return OperationResult::err(format!("Failed to parse generated key: {}", e)),
+    };
+
+    ctx.progress(80, "Verifying key protection");
+    // Never report a key as protected unless every secret key packet
+    // actually is encrypted.
+    let metadata = KeyMetadata {
+        user_id: profile.user_ids[0].clone(),
+        ..keyring::metadata_from_cert(&cert)
+    };
+    if !passphrase.is_empty() && metadata.protection.iter().any(|k| !k.encrypted) {
+        log::error!("Generated key {} is not fully passphrase protected", metadata.fingerprint);
+        return OperationResult::err("Failed to protect the generated key with the passphrase");
+    }
+
+    if ctx.is_cancelled() {
+        return ctx.cancelled();
+    }
+
+    ctx.progress(90, "Saving key to vault");
+    // Save key file (private key which includes public parts)
+    if let Err(e) = keyring::save_key_to_file(vault, &metadata.fingerprint, &private_key) {
+        return OperationResult::err(format!("Failed to save key file: {}", e));
     }
+
+    // Save metadata
+    match keyring::save_key_metadata(vault, &metadata) {
+        Ok(_) => OperationResult::ok(metadata),
+        Err(e) => OperationResult::err(format!("Failed to save key metadata: {}", e)),
+    }
+}
+
+// Converts an optional expiry timestamp into seconds from now, rejecting past dates.
+fn valid_seconds(expiry_timestamp: Option<i64>) -> Result<Option<u64>, String> {
+    expiry_timestamp
+        .map(|ts| crate::core::crypto::validity_until(ts).map(|d| d.as_secs()))
+        .transpose()
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn import_key(
     key_text: String,
     vault: State<'_, Vault>,
-) -> Result<OperationResult<KeyMetadata>, String> {
-    use sequoia_openpgp as openpgp;
-    use openpgp::parse::Parse;
-    use openpgp::Cert; // Can use Cert directly via openpgp::Cert too
-    
-    // Parse the key
-    let cert = match Cert::from_bytes(key_text.as_bytes()) {
-        Ok(c) => c,
-        Err(e) => return Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+) -> Result<OperationResult<ImportReport>, String> {
+    log::info!("IMPORT_KEY command called");
+
+    // The text may hold a whole keyring; every cert in it is merged into the
+    // vault on its own, so one broken cert does not abort the rest.
+    match keyring::import_keyring(&vault, key_text.as_bytes()) {
+        Ok(report) => {
+            log::info!(
+                "Imported keyring: {} new, {} updated, {} unchanged, {} failed",
+                report.new, report.updated, report.unchanged, report.failed
+            );
+            Ok(OperationResult::ok(report))
+        }
+        Err(e) => Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn import_key_file(
+    path: String,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<ImportReport>, String> {
+    log::info!("IMPORT_KEY_FILE command called for path: {}", path);
+
+    // Read raw bytes: binary keyrings (.gpg/.pgp/.kbx) are not valid UTF-8.
+    let data = match std::fs::read(&path) {
+        Ok(d) => d,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to read file: {}", e))),
     };
 
-    let fingerprint = cert.fingerprint().to_string().replace(" ", "");
-    
-    // Extract User ID (first one)
-    let mut name = String::new();
-    let mut email = String::new();
-    let comment = None;
-
-    if let Some(uid) = cert.userids().next() {
-        // uid is a Packet, uid.userid() returns &UserID
-        let s = String::from_utf8_lossy(uid.userid().value()).to_string();
-        
-        // Basic parsing of "Name (Comment) <Email>"
-        if let Some(start) = s.find('<') {
-            if let Some(end) = s.find('>') {
-                email = s[start+1..end].to_string();
-                name = s[0..start].trim().to_string();
-            }
-        } else {
-            name = s;
+    match keyring::import_key_data(&vault, &data) {
+        Ok(report) => Ok(OperationResult::ok(report)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to import keys: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn import_keys_from_text(
+    text: String,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<ImportReport>, String> {
+    log::info!("IMPORT_KEYS_FROM_TEXT command called");
+
+    // Scans arbitrary text, e.g. a pasted email, for every armored key block.
+    if crate::core::keyfile::armored_key_blocks(&text).is_empty() {
+        return Ok(OperationResult::err("No armored key blocks found in text".to_string()));
+    }
+
+    match keyring::import_key_data(&vault, text.as_bytes()) {
+        Ok(report) => Ok(OperationResult::ok(report)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to import keys: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn import_gnupg_home(
+    home_dir: Option<String>, // Defaults to $GNUPGHOME or ~/.gnupg; empty skips the home
+    key_files: Vec<String>,   // e.g. the output of `gpg --export-secret-keys`
+    ownertrust_file: Option<String>, // The output of `gpg --export-ownertrust`
+    operation_id: Option<String>,
+    app: AppHandle,
+) -> Result<OperationResult<GnupgImportReport>, String> {
+    log::info!(
+        "IMPORT_GNUPG_HOME command called for home: {:?}, {} key files, ownertrust: {:?}",
+        home_dir, key_files.len(), ownertrust_file
+    );
+
+    run_operation(app, operation_id, "import_gnupg_home", move |vault, ctx| {
+        import_gnupg(vault, ctx, home_dir, &key_files, ownertrust_file)
+    })
+    .await
+}
+
+fn import_gnupg(
+    vault: &Vault,
+    ctx: &OperationContext,
+    home_dir: Option<String>,
+    key_files: &[String],
+    ownertrust_file: Option<String>,
+) -> OperationResult<GnupgImportReport> {
+    use crate::core::gnupg;
+    use std::path::{Path, PathBuf};
+
+    // 1. Collect certs from the home and the export files
+    ctx.progress(5, "Reading GnuPG keyrings");
+    let home = match home_dir {
+        Some(dir) if dir.is_empty() => None,
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => gnupg::default_home(),
+    };
+
+    let mut keys = gnupg::GnupgKeys::default();
+    if let Some(home) = &home {
+        if let Err(e) = gnupg::read_home(home, &mut keys) {
+            return OperationResult::err(e.to_string());
         }
     }
-    
-    // Determine key type
-    let key_type = match cert.primary_key().pk_algo() {
-        openpgp::types::PublicKeyAlgorithm::EdDSA => KeyType::Ed25519,
-        openpgp::types::PublicKeyAlgorithm::RSAEncryptSign => {
-            // Detect RSA key size from the public key bits
-            let bits = cert.primary_key().mpis().bits().unwrap_or(4096);
-            if bits <= 2048 {
-                KeyType::Rsa2048
-            } else {
-                KeyType::Rsa4096
+    for file in key_files {
+        if let Err(e) = gnupg::read_file(Path::new(file), &mut keys) {
+            return OperationResult::err(e.to_string());
+        }
+    }
+
+    let ownertrust = match ownertrust_file {
+        Some(path) => {
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|text| gnupg::parse_ownertrust(&text));
+            match parsed {
+                Ok(entries) => entries,
+                Err(e) => return OperationResult::err(format!("Failed to read ownertrust file: {}", e)),
             }
         }
-        _ => KeyType::Rsa4096, // Default fallback
+        None => Vec::new(),
     };
 
-    // Use standard policy to check validity and get expiration
-    let p = openpgp::policy::StandardPolicy::new();
-    let expires_at = if let Ok(valid_cert) = cert.with_policy(&p, None) {
-        valid_cert.primary_key().key_expiration_time().map(|t| {
-            let datetime: chrono::DateTime<chrono::Utc> = t.into();
-            datetime.to_rfc3339()
-        })
-    } else {
-        None
-    };
+    if keys.certs.is_empty() && ownertrust.is_empty() {
+        return OperationResult::err("No keys found to import".to_string());
+    }
 
-    let creation_time: chrono::DateTime<chrono::Utc> = cert.primary_key().creation_time().into();
+    if ctx.is_cancelled() {
+        return ctx.cancelled();
+    }
 
-    let metadata = KeyMetadata {
-        fingerprint: fingerprint.clone(),
-        key_type,
-        user_id: UserId {
-            name,
-            email,
-            comment,
-        },
-        created_at: creation_time.to_rfc3339(),
-        expires_at,
-        is_private: cert.is_tsk(),
-        is_favorite: false,
-        tags: vec![],
+    // 2. Merge everything into the vault in one transaction
+    ctx.progress(30, &format!("Importing {} keys", keys.certs.len()));
+    let report = match keyring::import_certs(vault, keys.certs) {
+        Ok(r) => r,
+        Err(e) => return OperationResult::err(format!("Failed to import keys: {}", e)),
     };
 
-    // Save content
-    if let Err(e) = keyring::save_key_to_file(&vault, &fingerprint, &key_text) {
-        return Ok(OperationResult::err(format!("Failed to save key content: {}", e)));
+    // 3. Owner trust only applies to keys that are now in the vault
+    ctx.progress(90, "Applying owner trust");
+    let mut applied = 0;
+    let mut skipped = 0;
+    for (fingerprint, level) in ownertrust {
+        if keyring::load_key_content(vault, &fingerprint).is_err() {
+            skipped += 1;
+            continue;
+        }
+        if let Err(e) = trust::set_owner_trust(vault, &fingerprint, level) {
+            return OperationResult::err(format!("Failed to save owner trust for {}: {}", fingerprint, e));
+        }
+        applied += 1;
     }
 
-    // Save metadata
-    match keyring::save_key_metadata(&vault, &metadata) {
-        Ok(_) => Ok(OperationResult::ok(metadata)),
-        Err(e) => Ok(OperationResult::err(format!("Failed to save key metadata: {}", e))),
-    }
+    log::info!(
+        "Imported GnuPG keys: {} new, {} updated, {} unchanged, {} failed, {} owner trust entries",
+        report.new, report.updated, report.unchanged, report.failed, applied
+    );
+
+    OperationResult::ok(GnupgImportReport {
+        keys: report,
+        files: keys.files,
+        ownertrust_applied: applied,
+        ownertrust_skipped: skipped,
+        warnings: keys.warnings,
+    })
 }
 
 #[tauri::command]
@@ -269,7 +381,8 @@ pub async fn export_key(
                 }
             };
             
-        if let Err(e) = cert.serialize(&mut armor_writer) {
+        // export() skips local (non-exportable) certifications
+        if let Err(e) = cert.export(&mut armor_writer) {
             log::error!("Failed to serialize cert: {}", e);
             return Ok(OperationResult::err(format!("Failed to serialize cert: {}", e)));
         }
@@ -290,3 +403,1323 @@ pub async fn export_key(
         Ok(OperationResult::ok(public_key))
     }
 }
+
+#[tauri::command]
+pub async fn add_subkey(
+    fingerprint: String,
+    capability: String, // "sign" | "encrypt" | "encrypt_transport" | "encrypt_storage" | "authenticate"
+    key_type: String,
+    passphrase: String,
+    expiry_timestamp: Option<i64>, // Unix timestamp in seconds
+    operation_id: Option<String>,
+    app: AppHandle,
+) -> Result<OperationResult<String>, String> {
+    log::info!("ADD_SUBKEY command called for fingerprint: {}, capability: {}", fingerprint, capability);
+
+    run_operation(app, operation_id, "add_subkey", move |vault, ctx| {
+        add_subkey_blocking(vault, ctx, &fingerprint, &capability, &key_type, &passphrase, expiry_timestamp)
+    })
+    .await
+}
+
+fn add_subkey_blocking(
+    vault: &Vault,
+    ctx: &OperationContext,
+    fingerprint: &str,
+    capability: &str,
+    key_type: &str,
+    passphrase: &str,
+    expiry_timestamp: Option<i64>,
+) -> OperationResult<String> {
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    let valid_seconds = match valid_seconds(expiry_timestamp) {
+        Ok(v) => v,
+        Err(e) => return OperationResult::err(e),
+    };
+
+    let key_content = match keyring::load_key_content(vault, fingerprint) {
+        Ok(s) => s,
+        Err(e) => return OperationResult::err(e.to_string()),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return OperationResult::err(format!("Failed to parse key: {}", e)),
+    };
+
+    if !cert.is_tsk() {
+        return OperationResult::err("Subkeys can only be added to private keys".to_string());
+    }
+
+    ctx.progress(20, "Generating subkey");
+    let (cert, subkey_fingerprint) = match crate::core::crypto::add_subkey(
+        &cert,
+        passphrase,
+        capability,
+        key_type,
+        valid_seconds,
+    ) {
+        Ok(r) => r,
+        Err(e) => {
+            log::error!("Failed to add subkey to {}: {}", fingerprint, e);
+            return OperationResult::err(format!("Failed to add subkey: {}", e));
+        }
+    };
+
+    if ctx.is_cancelled() {
+        return ctx.cancelled();
+    }
+
+    let updated = match crate::core::crypto::armor_tsk(&cert) {
+        Ok(s) => s,
+        Err(e) => return OperationResult::err(format!("Failed to serialize key: {}", e)),
+    };
+
+    if let Err(e) = keyring::save_key_to_file(vault, fingerprint, &updated) {
+        return OperationResult::err(format!("Failed to save key content: {}", e));
+    }
+
+    if let Err(e) = refresh_protection(vault, fingerprint, &cert) {
+        return OperationResult::err(format!("Failed to save key metadata: {}", e));
+    }
+
+    log::info!("Added subkey {} to {}", subkey_fingerprint, fingerprint);
+    OperationResult::ok(subkey_fingerprint.to_hex())
+}
+
+#[tauri::command]
+pub async fn revoke_subkey(
+    fingerprint: String,
+    subkey_fingerprint: String,
+    passphrase: String,
+    reason: String, // "superseded" | "compromised" | "retired" | "unspecified"
+    message: Option<String>,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<bool>, String> {
+    log::info!("REVOKE_SUBKEY command called for fingerprint: {}, subkey: {}", fingerprint, subkey_fingerprint);
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    let key_content = match keyring::load_key_content(&vault, &fingerprint) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+    };
+
+    if !cert.is_tsk() {
+        return Ok(OperationResult::err("Subkeys can only be revoked on private keys".to_string()));
+    }
+
+    let cert = match crate::core::crypto::revoke_subkey(
+        &cert,
+        &passphrase,
+        &subkey_fingerprint,
+        &reason,
+        message.as_deref().unwrap_or(""),
+    ) {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Failed to revoke subkey {} of {}: {}", subkey_fingerprint, fingerprint, e);
+            return Ok(OperationResult::err(format!("Failed to revoke subkey: {}", e)));
+        }
+    };
+
+    let updated = match crate::core::crypto::armor_tsk(&cert) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to serialize key: {}", e))),
+    };
+
+    match keyring::save_key_to_file(&vault, &fingerprint, &updated) {
+        Ok(_) => Ok(OperationResult::ok(true)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to save key content: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn change_passphrase(
+    fingerprint: String,
+    old_passphrase: String,
+    new_passphrase: String,
+    confirm_remove_protection: bool,
+    operation_id: Option<String>,
+    app: AppHandle,
+) -> Result<OperationResult<bool>, String> {
+    log::info!("CHANGE_PASSPHRASE command called for fingerprint: {}", fingerprint);
+
+    run_operation(app, operation_id, "change_passphrase", move |vault, ctx| {
+        change_passphrase_blocking(
+            vault,
+            ctx,
+            &fingerprint,
+            &old_passphrase,
+            &new_passphrase,
+            confirm_remove_protection,
+        )
+    })
+    .await
+}
+
+fn change_passphrase_blocking(
+    vault: &Vault,
+    ctx: &OperationContext,
+    fingerprint: &str,
+    old_passphrase: &str,
+    new_passphrase: &str,
+    confirm_remove_protection: bool,
+) -> OperationResult<bool> {
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    // Removing protection leaves the secret key in clear text inside the vault,
+    // so the frontend has to ask for it explicitly.
+    if new_passphrase.is_empty() && !confirm_remove_protection {
+        return OperationResult::err(
+            "Removing the passphrase requires explicit confirmation".to_string(),
+        );
+    }
+
+    let key_content = match keyring::load_key_content(vault, fingerprint) {
+        Ok(s) => s,
+        Err(e) => return OperationResult::err(e.to_string()),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return OperationResult::err(format!("Failed to parse key: {}", e)),
+    };
+
+    if !cert.is_tsk() {
+        return OperationResult::err("Selected key is a public key, it has no passphrase.".to_string());
+    }
+
+    ctx.progress(20, "Re-encrypting secret keys");
+    let cert = match crate::core::crypto::change_passphrase(cert, old_passphrase, new_passphrase) {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Failed to change passphrase for {}: {}", fingerprint, e);
+            return OperationResult::err(format!("Failed to change passphrase: {}", e));
+        }
+    };
+
+    if ctx.is_cancelled() {
+        return ctx.cancelled();
+    }
+
+    let updated = match crate::core::crypto::armor_tsk(&cert) {
+        Ok(s) => s,
+        Err(e) => return OperationResult::err(format!("Failed to serialize key: {}", e)),
+    };
+
+    if let Err(e) = keyring::save_key_to_file(vault, fingerprint, &updated) {
+        return OperationResult::err(format!("Failed to save key content: {}", e));
+    }
+
+    match refresh_protection(vault, fingerprint, &cert) {
+        Ok(_) => OperationResult::ok(true),
+        Err(e) => OperationResult::err(format!("Failed to save key metadata: {}", e)),
+    }
+}
+
+// Keeps the stored protection status in sync after the secret key packets change.
+fn refresh_protection(
+    vault: &Vault,
+    fingerprint: &str,
+    cert: &sequoia_openpgp::Cert,
+) -> anyhow::Result<()> {
+    let mut metadata = keyring::load_key_metadata(vault, fingerprint)?;
+    metadata.protection = crate::core::crypto::protection_status(cert);
+    keyring::save_key_metadata(vault, &metadata)
+}
+
+#[tauri::command]
+pub async fn export_keys(
+    fingerprints: Vec<String>,
+    output_path: String,
+    armor: bool,
+    minimal: bool,
+    all_public: bool, // Export the whole public keyring, ignoring `fingerprints`
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyExport>, String> {
+    log::info!(
+        "EXPORT_KEYS command called for {} keys, all_public: {}, armor: {}, minimal: {}",
+        fingerprints.len(), all_public, armor, minimal
+    );
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    let contents = if all_public {
+        match keyring::load_all_key_contents(&vault) {
+            Ok(c) => c,
+            Err(e) => return Ok(OperationResult::err(format!("Failed to load keys: {}", e))),
+        }
+    } else {
+        let mut contents = Vec::new();
+        for fingerprint in &fingerprints {
+            match keyring::load_key_content(&vault, fingerprint) {
+                Ok(c) => contents.push((fingerprint.clone(), c)),
+                Err(_) => return Ok(OperationResult::err(format!("Key not found: {}", fingerprint))),
+            }
+        }
+        contents
+    };
+
+    if contents.is_empty() {
+        return Ok(OperationResult::err("No keys selected for export".to_string()));
+    }
+
+    let mut certs = Vec::new();
+    let mut unminimized = Vec::new();
+    for (fingerprint, content) in contents {
+        let cert = match openpgp::Cert::from_bytes(content.as_bytes()) {
+            Ok(c) => c,
+            Err(e) => return Ok(OperationResult::err(format!("Failed to parse key {}: {}", fingerprint, e))),
+        };
+
+        // Keys the policy rejects cannot be minimized; export them as stored
+        // rather than failing the whole export.
+        let cert = if minimal {
+            match crate::core::crypto::minimize_cert(&cert) {
+                Ok(c) => c,
+                Err(e) => {
+                    log::warn!("Exporting key {} unminimized: {}", fingerprint, e);
+                    unminimized.push(fingerprint);
+                    cert
+                }
+            }
+        } else {
+            cert
+        };
+        certs.push(cert);
+    }
+
+    let bytes = match crate::core::crypto::serialize_public_keyring(&certs, armor) {
+        Ok(b) => b,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to serialize keys: {}", e))),
+    };
+
+    match std::fs::write(&output_path, bytes) {
+        Ok(_) => Ok(OperationResult::ok(KeyExport {
+            exported: certs.len(),
+            unminimized,
+        })),
+        Err(e) => Ok(OperationResult::err(format!("Failed to write file: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn export_vault(
+    output_dir: String,
+    armor: bool,
+    include_secret: bool,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<VaultExport>, String> {
+    log::info!(
+        "EXPORT_VAULT command called for dir: {}, armor: {}, include_secret: {}",
+        output_dir, armor, include_secret
+    );
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+    use crate::core::{crypto, gnupg};
+
+    let contents = match keyring::load_all_key_contents(&vault) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to load keys: {}", e))),
+    };
+    if contents.is_empty() {
+        return Ok(OperationResult::err("The vault holds no keys".to_string()));
+    }
+
+    // Unreadable keys are left out and listed in the report.
+    let mut certs = Vec::new();
+    let mut skipped = Vec::new();
+    for (fingerprint, content) in contents {
+        match openpgp::Cert::from_bytes(content.as_bytes()) {
+            Ok(c) => certs.push(c),
+            Err(e) => {
+                log::warn!("Skipping unreadable key {} in vault export: {}", fingerprint, e);
+                skipped.push(fingerprint);
+            }
+        }
+    }
+    if certs.is_empty() {
+        return Ok(OperationResult::err("The vault holds no readable keys".to_string()));
+    }
+
+    // 1. Keyrings in the layout GnuPG exports and imports
+    let dir = std::path::Path::new(&output_dir);
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        return Ok(OperationResult::err(format!("Failed to create {}: {}", output_dir, e)));
+    }
+    let extension = if armor { "asc" } else { "gpg" };
+
+    let public_path = dir.join(format!("public-keyring.{}", extension));
+    let written = crypto::serialize_public_keyring(&certs, armor)
+        .and_then(|bytes| Ok(std::fs::write(&public_path, bytes)?));
+    if let Err(e) = written {
+        return Ok(OperationResult::err(format!("Failed to write public keyring: {}", e)));
+    }
+
+    let mut secret_keys = certs.iter().filter(|c| c.is_tsk()).count();
+    let secret_path = if include_secret && secret_keys > 0 {
+        let path = dir.join(format!("secret-keyring.{}", extension));
+        let written = crypto::serialize_secret_keyring(&certs, armor)
+            .and_then(|bytes| Ok(std::fs::write(&path, bytes)?));
+        if let Err(e) = written {
+            return Ok(OperationResult::err(format!("Failed to write secret keyring: {}", e)));
+        }
+        Some(path.display().to_string())
+    } else {
+        secret_keys = 0;
+        None
+    };
+
+    // A secret keyring left by an earlier export would otherwise sit next
+    // to this one as if it belonged to it.
+    for stale in ["asc", "gpg"] {
+        let path = dir.join(format!("secret-keyring.{}", stale));
+        if secret_path.is_some() && stale == extension {
+            continue;
+        }
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Ok(OperationResult::err(format!("Failed to remove {}: {}", path.display(), e)));
+            }
+        }
+    }
+
+    // 2. Owner trust of the exported keys, for `gpg --import-ownertrust`
+    let owner_trust = match trust::load_owner_trust(&vault) {
+        Ok(t) => t,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to load owner trust: {}", e))),
+    };
+    let mut entries: Vec<(String, OwnerTrust)> = certs
+        .iter()
+        .filter_map(|c| {
+            let fingerprint = c.fingerprint().to_hex();
+            owner_trust
+                .get(&fingerprint)
+                .filter(|t| **t != OwnerTrust::Unknown)
+                .map(|t| (fingerprint, *t))
+        })
+        .collect();
+    entries.sort();
+
+    let ownertrust_path = dir.join("ownertrust.txt");
+    if let Err(e) = std::fs::write(&ownertrust_path, gnupg::format_ownertrust(&entries)) {
+        return Ok(OperationResult::err(format!("Failed to write ownertrust file: {}", e)));
+    }
+
+    log::info!(
+        "Exported {} public keys, {} secret keys and {} owner trust entries to {}",
+        certs.len(), secret_keys, entries.len(), output_dir
+    );
+
+    Ok(OperationResult::ok(VaultExport {
+        public_keyring: public_path.display().to_string(),
+        public_keys: certs.len(),
+        secret_keyring: secret_path,
+        secret_keys,
+        ownertrust_file: ownertrust_path.display().to_string(),
+        ownertrust_entries: entries.len(),
+        skipped,
+    }))
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn certify_key(
+    target_fingerprint: String,
+    signer_fingerprint: String,
+    passphrase: String,
+    user_ids: Vec<String>, // Empty to certify every user ID
+    trust_depth: u8,
+    trust_amount: Option<u8>,
+    expiry_timestamp: Option<i64>, // Unix timestamp in seconds
+    local: bool,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<bool>, String> {
+    log::info!(
+        "CERTIFY_KEY command called for target: {}, signer: {}, depth: {}, local: {}",
+        target_fingerprint, signer_fingerprint, trust_depth, local
+    );
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    let valid_seconds = match valid_seconds(expiry_timestamp) {
+        Ok(v) => v,
+        Err(e) => return Ok(OperationResult::err(e)),
+    };
+
+    let mut certs = Vec::new();
+    for fingerprint in [&signer_fingerprint, &target_fingerprint] {
+        let content = match keyring::load_key_content(&vault, fingerprint) {
+            Ok(s) => s,
+            Err(_) => return Ok(OperationResult::err(format!("Key not found: {}", fingerprint))),
+        };
+        match openpgp::Cert::from_bytes(content.as_bytes()) {
+            Ok(c) => certs.push(c),
+            Err(e) => return Ok(OperationResult::err(format!("Failed to parse key {}: {}", fingerprint, e))),
+        }
+    }
+    let target = certs.pop().unwrap();
+    let signer = certs.pop().unwrap();
+
+    if !signer.is_tsk() {
+        return Ok(OperationResult::err("Certifying requires one of your private keys".to_string()));
+    }
+
+    let options = crate::core::crypto::CertifyOptions {
+        user_ids: &user_ids,
+        trust_depth,
+        trust_amount: trust_amount.unwrap_or(120),
+        valid_seconds,
+        exportable: !local,
+    };
+
+    let certified = match crate::core::crypto::certify_userids(&signer, &passphrase, &target, &options) {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Failed to certify {}: {}", target_fingerprint, e);
+            return Ok(OperationResult::err(format!("Failed to certify key: {}", e)));
+        }
+    };
+
+    let content = if certified.is_tsk() {
+        crate::core::crypto::armor_tsk(&certified)
+    } else {
+        crate::core::crypto::armor_public(&certified)
+    };
+    let content = match content {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to serialize key: {}", e))),
+    };
+
+    match keyring::save_key_to_file(&vault, &target_fingerprint, &content) {
+        Ok(_) => Ok(OperationResult::ok(true)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to save key content: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn set_owner_trust(
+    fingerprint: String,
+    trust: String, // "never" | "unknown" | "marginal" | "full" | "ultimate"
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<bool>, String> {
+    log::info!("SET_OWNER_TRUST command called for fingerprint: {}, trust: {}", fingerprint, trust);
+
+    let level: OwnerTrust = match trust.parse() {
+        Ok(l) => l,
+        Err(e) => return Ok(OperationResult::err(e)),
+    };
+
+    if keyring::load_key_content(&vault, &fingerprint).is_err() {
+        return Ok(OperationResult::err("Key not found".to_string()));
+    }
+
+    match trust::set_owner_trust(&vault, &fingerprint, level) {
+        Ok(_) => Ok(OperationResult::ok(true)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to save owner trust: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn get_key_details(
+    fingerprint: String,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyDetails>, String> {
+    log::info!("GET_KEY_DETAILS command called for fingerprint: {}", fingerprint);
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    let key_content = match keyring::load_key_content(&vault, &fingerprint) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+    };
+
+    let vault_certs = match trust::load_vault_certs(&vault) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to load keys: {}", e))),
+    };
+
+    match crate::core::inspect::key_details(&cert, &vault_certs) {
+        Ok(details) => Ok(OperationResult::ok(details)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to inspect key: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn get_fingerprint_aids(
+    fingerprint: String,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<FingerprintAids>, String> {
+    log::info!("GET_FINGERPRINT_AIDS command called for fingerprint: {}", fingerprint);
+
+    use sequoia_openpgp as openpgp;
+    use crate::core::fingerprint as aids;
+
+    let metadata = match keyring::load_key_metadata(&vault, &fingerprint) {
+        Ok(m) => m,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let fp: openpgp::Fingerprint = match metadata.fingerprint.parse() {
+        Ok(f) => f,
+        Err(e) => return Ok(OperationResult::err(format!("Invalid fingerprint: {}", e))),
+    };
+
+    let uri = aids::uri(&fp);
+    let qr_svg = match aids::qr_svg(&uri) {
+        Ok(svg) => svg,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to render QR code: {}", e))),
+    };
+
+    Ok(OperationResult::ok(FingerprintAids {
+        fingerprint: fp.to_hex(),
+        spaced: fp.to_spaced_hex(),
+        words: aids::words(&fp).into_iter().map(String::from).collect(),
+        randomart: aids::randomart(&fp, &metadata.key_type.to_string()),
+        uri,
+        qr_svg,
+    }))
+}
+
+#[tauri::command]
+pub async fn verify_fingerprint_uri(
+    uri: String,
+    expected_fingerprint: Option<String>, // The key the user meant to verify, if any
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyMetadata>, String> {
+    log::info!("VERIFY_FINGERPRINT_URI command called");
+
+    let fingerprint = match crate::core::fingerprint::parse_uri(&uri) {
+        Ok(f) => f.to_hex(),
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    if let Some(expected) = expected_fingerprint {
+        if !expected.eq_ignore_ascii_case(&fingerprint) {
+            log::warn!("Scanned fingerprint {} does not match key {}", fingerprint, expected);
+            return Ok(OperationResult::err(format!(
+                "Fingerprint mismatch: scanned {}, expected {}",
+                fingerprint, expected
+            )));
+        }
+    }
+
+    if keyring::load_key_content(&vault, &fingerprint).is_err() {
+        return Ok(OperationResult::err(format!(
+            "Key {} is not in the vault, import it first",
+            fingerprint
+        )));
+    }
+
+    let verified_at = chrono::Utc::now().to_rfc3339();
+    match keyring::update_key_metadata(&vault, &fingerprint, |m| m.verified_at = Some(verified_at)) {
+        Ok(metadata) => {
+            log::info!("Marked key {} as verified", fingerprint);
+            Ok(OperationResult::ok(metadata))
+        }
+        Err(e) => Ok(OperationResult::err(format!("Failed to mark key as verified: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn toggle_favorite(
+    fingerprint: String,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyMetadata>, String> {
+    log::info!("TOGGLE_FAVORITE command called for fingerprint: {}", fingerprint);
+
+    match keyring::update_key_metadata(&vault, &fingerprint, |m| m.is_favorite = !m.is_favorite) {
+        Ok(metadata) => Ok(OperationResult::ok(metadata)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to update favorite: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn add_key_tag(
+    fingerprint: String,
+    tag: String,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyMetadata>, String> {
+    log::info!("ADD_KEY_TAG command called for fingerprint: {}, tag: {}", fingerprint, tag);
+
+    let tag = tag.trim().to_string();
+    if tag.is_empty() {
+        return Ok(OperationResult::err("Tag must not be empty".to_string()));
+    }
+
+    let result = keyring::update_key_metadata(&vault, &fingerprint, |m| {
+        if !m.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            m.tags.push(tag);
+        }
+    });
+
+    match result {
+        Ok(metadata) => Ok(OperationResult::ok(metadata)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to add tag: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn remove_key_tag(
+    fingerprint: String,
+    tag: String,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyMetadata>, String> {
+    log::info!("REMOVE_KEY_TAG command called for fingerprint: {}, tag: {}", fingerprint, tag);
+
+    let tag = tag.trim();
+    match keyring::update_key_metadata(&vault, &fingerprint, |m| m.tags.retain(|t| !t.eq_ignore_ascii_case(tag))) {
+        Ok(metadata) => Ok(OperationResult::ok(metadata)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to remove tag: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn set_key_notes(
+    fingerprint: String,
+    notes: Option<String>, // None or blank clears the notes
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyMetadata>, String> {
+    log::info!("SET_KEY_NOTES command called for fingerprint: {}", fingerprint);
+
+    let notes = notes.filter(|n| !n.trim().is_empty());
+    match keyring::update_key_metadata(&vault, &fingerprint, |m| m.notes = notes) {
+        Ok(metadata) => Ok(OperationResult::ok(metadata)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to save notes: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn search_keys(
+    query: KeySearchQuery,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeySearchResult>, String> {
+    log::info!("SEARCH_KEYS command called with {:?}", query);
+
+    match search::search_keys(&vault, &query) {
+        Ok(mut result) => {
+            // Only the returned page is annotated, from the vault's cached
+            // validity, so paging does not re-parse the whole vault.
+            if let Err(e) = trust::annotate_keys(&vault, &mut result.keys) {
+                log::error!("Failed to compute key validity: {}", e);
+            }
+            Ok(OperationResult::ok(result))
+        }
+        Err(e) => Ok(OperationResult::err(format!("Failed to search keys: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn audit_keyring(
+    expiring_within_days: Option<u32>, // Defaults to 30
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<AuditReport>, String> {
+    log::info!("AUDIT_KEYRING command called");
+
+    match audit::audit_keyring(&vault, expiring_within_days.unwrap_or(30)) {
+        Ok(report) => {
+            log::info!("Audited {} keys, {} findings", report.keys_checked, report.findings.len());
+            Ok(OperationResult::ok(report))
+        }
+        Err(e) => Ok(OperationResult::err(format!("Failed to audit keyring: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn extend_key_expiry(
+    fingerprint: String,
+    passphrase: String,
+    expiry_timestamp: Option<i64>, // Unix timestamp in seconds, None removes the expiry
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyMetadata>, String> {
+    log::info!("EXTEND_KEY_EXPIRY command called for fingerprint: {}", fingerprint);
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    let expiration = match valid_seconds(expiry_timestamp) {
+        Ok(v) => v.map(|secs| std::time::SystemTime::now() + std::time::Duration::from_secs(secs)),
+        Err(e) => return Ok(OperationResult::err(e)),
+    };
+
+    let key_content = match keyring::load_key_content(&vault, &fingerprint) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+    };
+
+    if !cert.is_tsk() {
+        return Ok(OperationResult::err("Only private keys can be extended".to_string()));
+    }
+
+    let cert = match crate::core::crypto::set_expiry(&cert, &passphrase, expiration) {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Failed to extend expiry of {}: {}", fingerprint, e);
+            return Ok(OperationResult::err(format!("Failed to extend expiry: {}", e)));
+        }
+    };
+
+    let updated = match crate::core::crypto::armor_tsk(&cert) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to serialize key: {}", e))),
+    };
+
+    if let Err(e) = keyring::save_key_to_file(&vault, &fingerprint, &updated) {
+        return Ok(OperationResult::err(format!("Failed to save key content: {}", e)));
+    }
+
+    let expires_at = keyring::metadata_from_cert(&cert).expires_at;
+    match keyring::update_key_metadata(&vault, &fingerprint, |m| m.expires_at = expires_at) {
+        Ok(metadata) => Ok(OperationResult::ok(metadata)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to save key metadata: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn export_paper_backup(
+    fingerprint: String,
+    format: String, // "text" | "pdf"
+    output_path: Option<String>, // Required for "pdf"
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<String>, String> {
+    log::info!("EXPORT_PAPER_BACKUP command called for fingerprint: {}, format: {}", fingerprint, format);
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+    use crate::core::{paper, pdf};
+
+    let key_content = match keyring::load_key_content(&vault, &fingerprint) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+    };
+
+    // 1. Secret key material only, rendered as checksummed lines
+    let sheet = match paper::extract_secrets(&cert) {
+        Ok(data) => paper::to_text(&cert, &data),
+        Err(e) => return Ok(OperationResult::err(format!("Failed to extract secret keys: {}", e))),
+    };
+
+    // 2. Write the requested format; the text is always returned for previews
+    let bytes = match format.as_str() {
+        "text" => sheet.clone().into_bytes(),
+        "pdf" => {
+            let lines: Vec<String> = sheet.lines().map(String::from).collect();
+            pdf::PdfDocument::text_pages(&lines, 9.0).to_bytes()
+        }
+        other => return Ok(OperationResult::err(format!("Unsupported paper backup format: {}", other))),
+    };
+
+    match output_path {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, bytes) {
+                log::error!("Failed to write paper backup to {}: {}", path, e);
+                return Ok(OperationResult::err(format!("Failed to write file: {}", e)));
+            }
+        }
+        None if format == "pdf" => {
+            return Ok(OperationResult::err("An output path is required for PDF backups".to_string()));
+        }
+        None => {}
+    }
+
+    Ok(OperationResult::ok(sheet))
+}
+
+#[tauri::command]
+pub async fn export_key_sheet(
+    fingerprint: String,
+    format: String, // "html" | "pdf"
+    qr_content: Option<String>, // "public_key" (default) | "fingerprint"
+    output_path: Option<String>, // Required for "pdf"
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeySheetExport>, String> {
+    log::info!("EXPORT_KEY_SHEET command called for fingerprint: {}, format: {}", fingerprint, format);
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+    use crate::core::keysheet::{self, QrContent};
+
+    let qr_content = match qr_content.as_deref() {
+        None | Some("public_key") => QrContent::PublicKey,
+        Some("fingerprint") => QrContent::Fingerprint,
+        Some(other) => return Ok(OperationResult::err(format!("Unsupported QR code content: {}", other))),
+    };
+
+    let metadata = match keyring::load_key_metadata(&vault, &fingerprint) {
+        Ok(m) => m,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let key_content = match keyring::load_key_content(&vault, &fingerprint) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c.strip_secret_key_material(),
+        Err(e) => return Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+    };
+
+    // 1. Collect the sheet; a public key too large for a QR code falls back
+    //    to the fingerprint URI
+    let sheet = match keysheet::build(&cert, &metadata, qr_content) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to build key sheet: {}", e))),
+    };
+    if sheet.qr_content != qr_content {
+        log::info!("Key {} is too large for a QR code, using its fingerprint", fingerprint);
+    }
+
+    // 2. Write the requested format; the HTML is always returned for previews
+    let html = sheet.to_html();
+    let bytes = match format.as_str() {
+        "html" => html.clone().into_bytes(),
+        "pdf" => sheet.to_pdf(),
+        other => return Ok(OperationResult::err(format!("Unsupported key sheet format: {}", other))),
+    };
+
+    match output_path {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, bytes) {
+                log::error!("Failed to write key sheet to {}: {}", path, e);
+                return Ok(OperationResult::err(format!("Failed to write file: {}", e)));
+            }
+        }
+        None if format == "pdf" => {
+            return Ok(OperationResult::err("An output path is required for PDF key sheets".to_string()));
+        }
+        None => {}
+    }
+
+    Ok(OperationResult::ok(KeySheetExport {
+        qr_content: sheet.qr_content.as_str().to_string(),
+        html,
+    }))
+}
+
+#[tauri::command]
+pub async fn import_paper_backup(
+    paper_text: String,
+    public_key: Option<String>, // Defaults to the public key stored in the vault
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyMetadata>, String> {
+    log::info!("IMPORT_PAPER_BACKUP command called");
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+    use crate::core::paper;
+
+    let data = match paper::from_text(&paper_text) {
+        Ok(d) => d,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to read paper backup: {}", e))),
+    };
+
+    // 1. Find the public cert: given explicitly, or looked up by the first
+    //    fingerprint in the backup
+    let public_key = match public_key {
+        Some(k) => k,
+        None => {
+            let fingerprint = match data.get(2..22) {
+                Some(fp) => openpgp::Fingerprint::from_bytes(fp).to_hex(),
+                None => return Ok(OperationResult::err("Paper backup is truncated".to_string())),
+            };
+            match keyring::load_key_content(&vault, &fingerprint) {
+                Ok(k) => k,
+                Err(_) => {
+                    return Ok(OperationResult::err(format!(
+                        "Public key {} is not in the vault, please provide it",
+                        fingerprint
+                    )))
+                }
+            }
+        }
+    };
+
+    let cert = match openpgp::Cert::from_bytes(public_key.as_bytes()) {
+        Ok(c) => c.strip_secret_key_material(),
+        Err(e) => return Ok(OperationResult::err(format!("Failed to parse public key: {}", e))),
+    };
+
+    // 2. Rebuild the secret keys and merge them into the vault
+    let cert = match paper::restore_secrets(&cert, &data) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to restore secret keys: {}", e))),
+    };
+
+    let fingerprint = cert.fingerprint().to_hex();
+    if let Err(e) = keyring::import_cert(&vault, cert) {
+        return Ok(OperationResult::err(format!("Failed to import restored key: {}", e)));
+    }
+
+    match keyring::load_key_metadata(&vault, &fingerprint) {
+        Ok(metadata) => Ok(OperationResult::ok(metadata)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to load key metadata: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn export_key_shares(
+    fingerprint: String,
+    threshold: u8,
+    shares: u8,
+    format: String, // "text" | "armor"
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<Vec<String>>, String> {
+    log::info!(
+        "EXPORT_KEY_SHARES command called for fingerprint: {}, {} of {}",
+        fingerprint, threshold, shares
+    );
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+    use openpgp::serialize::SerializeInto;
+    use crate::core::shamir;
+
+    let key_content = match keyring::load_key_content(&vault, &fingerprint) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+    };
+
+    if !cert.is_tsk() {
+        return Ok(OperationResult::err("Only private keys can be split into shares".to_string()));
+    }
+
+    // 1. The binary TSK, exactly as export_key would export it
+    let secret: openpgp::crypto::mem::Protected = match cert.as_tsk().to_vec() {
+        Ok(b) => b.into(),
+        Err(e) => return Ok(OperationResult::err(format!("Failed to serialize key: {}", e))),
+    };
+
+    let key_shares = match shamir::split(&secret, &cert.fingerprint(), threshold, shares) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to split key: {}", e))),
+    };
+
+    // 2. Encode every share on its own so they can be handed out separately
+    let encoded: Result<Vec<String>, String> = key_shares
+        .iter()
+        .map(|share| match format.as_str() {
+            "text" => Ok(share.to_text(shares)),
+            "armor" => share.to_armor(shares).map_err(|e| e.to_string()),
+            other => Err(format!("Unsupported share format: {}", other)),
+        })
+        .collect();
+
+    match encoded {
+        Ok(encoded) => Ok(OperationResult::ok(encoded)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to encode shares: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn recover_key_from_shares(
+    shares: Vec<String>,
+) -> Result<OperationResult<String>, String> {
+    log::info!("RECOVER_KEY_FROM_SHARES command called with {} shares", shares.len());
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+    use crate::core::shamir::{self, KeyShare};
+
+    let mut key_shares = Vec::new();
+    for (i, text) in shares.iter().enumerate() {
+        match KeyShare::parse(text) {
+            Ok(share) => key_shares.push(share),
+            Err(e) => return Ok(OperationResult::err(format!("Failed to read share {}: {}", i + 1, e))),
+        }
+    }
+
+    let secret = match shamir::combine(&key_shares) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to combine shares: {}", e))),
+    };
+
+    // The shares carry the fingerprint, so a wrong combination is caught here.
+    let cert = match openpgp::Cert::from_bytes(&secret) {
+        Ok(c) if c.fingerprint() == key_shares[0].fingerprint && c.is_tsk() => c,
+        _ => return Ok(OperationResult::err("The shares did not restore a valid private key".to_string())),
+    };
+
+    match crate::core::crypto::armor_tsk(&cert) {
+        Ok(armored) => Ok(OperationResult::ok(armored)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to serialize key: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn export_wkd(
+    fingerprints: Vec<String>,
+    output_dir: String,
+    domain: String,
+    layout: String, // "direct" | "advanced" | "both"
+    minimal: bool,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<WkdReport>, String> {
+    log::info!(
+        "EXPORT_WKD command called for {} keys, domain: {}, layout: {}",
+        fingerprints.len(), domain, layout
+    );
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    let layouts = match layout.as_str() {
+        "direct" => vec![WkdLayout::Direct],
+        "advanced" => vec![WkdLayout::Advanced],
+        "both" => vec![WkdLayout::Direct, WkdLayout::Advanced],
+        other => return Ok(OperationResult::err(format!("Unsupported WKD layout: {}", other))),
+    };
+
+    if fingerprints.is_empty() {
+        return Ok(OperationResult::err("No keys selected for export".to_string()));
+    }
+
+    // Same preparation as export_keys: the public keyring serialization
+    // drops secrets and local certifications.
+    let mut certs = Vec::new();
+    for fingerprint in &fingerprints {
+        let content = match keyring::load_key_content(&vault, fingerprint) {
+            Ok(c) => c,
+            Err(_) => return Ok(OperationResult::err(format!("Key not found: {}", fingerprint))),
+        };
+        let cert = match openpgp::Cert::from_bytes(content.as_bytes()) {
+            Ok(c) => c,
+            Err(e) => return Ok(OperationResult::err(format!("Failed to parse key {}: {}", fingerprint, e))),
+        };
+        // As in export_keys, a key that can't be minimized is published as
+        // stored rather than blocking the others.
+        let cert = if minimal {
+            match crate::core::crypto::minimize_cert(&cert) {
+                Ok(c) => c,
+                Err(e) => {
+                    log::warn!("Publishing key {} unminimized: {}", fingerprint, e);
+                    cert
+                }
+            }
+        } else {
+            cert
+        };
+        certs.push(cert);
+    }
+
+    match wkd::generate(&certs, std::path::Path::new(&output_dir), &domain, &layouts) {
+        Ok(report) => {
+            log::info!("Wrote {} WKD entries to {}", report.entries.len(), output_dir);
+            Ok(OperationResult::ok(report))
+        }
+        Err(e) => {
+            log::error!("Failed to write WKD directory to {}: {}", output_dir, e);
+            Ok(OperationResult::err(format!("Failed to write WKD directory: {}", e)))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn autocrypt_header(
+    fingerprint: String,
+    addr: Option<String>, // Defaults to the primary user ID's email
+    prefer_encrypt_mutual: bool,
+    gossip: bool,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<String>, String> {
+    log::info!("AUTOCRYPT_HEADER command called for fingerprint: {}", fingerprint);
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    let key_content = match keyring::load_key_content(&vault, &fingerprint) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+    };
+
+    let addr = match addr {
+        Some(a) => a,
+        None => {
+            let metadata = keyring::metadata_from_cert(&cert);
+            if metadata.user_id.email.is_empty() {
+                return Ok(OperationResult::err("Key has no email address".to_string()));
+            }
+            metadata.user_id.email
+        }
+    };
+
+    match autocrypt::header(&cert, &addr, prefer_encrypt_mutual, gossip) {
+        Ok(header) => Ok(OperationResult::ok(header)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to build Autocrypt header: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn import_autocrypt_headers(
+    text: String, // Raw mail headers, or just the header values
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<ImportReport>, String> {
+    log::info!("IMPORT_AUTOCRYPT_HEADERS command called");
+
+    use crate::models::{ImportEntry, ImportStatus};
+
+    let headers = autocrypt::parse_headers(&text);
+    if headers.is_empty() {
+        return Ok(OperationResult::err("No Autocrypt headers found in text".to_string()));
+    }
+
+    // Every header is imported on its own, like the certs of a keyring.
+    let mut report = ImportReport::default();
+    for header in headers {
+        let cert = match header.and_then(|h| autocrypt::header_cert(&h)) {
+            Ok(c) => c,
+            Err(e) => {
+                report.push(ImportEntry {
+                    fingerprint: None,
+                    user_id: None,
+                    status: ImportStatus::Failed,
+                    error: Some(e.to_string()),
+                });
+                continue;
+            }
+        };
+
+        let fingerprint = cert.fingerprint().to_hex();
+        let user_id = cert
+            .userids()
+            .next()
+            .map(|uid| String::from_utf8_lossy(uid.userid().value()).to_string());
+
+        report.push(match keyring::import_cert(&vault, cert) {
+            Ok(status) => ImportEntry {
+                fingerprint: Some(fingerprint),
+                user_id,
+                status,
+                error: None,
+            },
+            Err(e) => {
+                log::error!("Failed to import Autocrypt key {}: {}", fingerprint, e);
+                ImportEntry {
+                    fingerprint: Some(fingerprint),
+                    user_id,
+                    status: ImportStatus::Failed,
+                    error: Some(e.to_string()),
+                }
+            }
+        });
+    }
+
+    Ok(OperationResult::ok(report))
+}
+
+#[tauri::command]
+pub async fn create_autocrypt_setup_message(
+    fingerprint: String,
+    passphrase: String, // Current passphrase, the key is sent unprotected inside the message
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<AutocryptSetupMessage>, String> {
+    log::info!("CREATE_AUTOCRYPT_SETUP_MESSAGE command called for fingerprint: {}", fingerprint);
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    let key_content = match keyring::load_key_content(&vault, &fingerprint) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+    };
+
+    if !cert.is_tsk() {
+        return Ok(OperationResult::err("Key has no secret key material".to_string()));
+    }
+
+    // 1. The setup code takes over the passphrase's job
+    let cert = match crate::core::crypto::change_passphrase(cert, &passphrase, "") {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to unlock key: {}", e))),
+    };
+
+    // 2. Encrypt the key with a fresh setup code
+    let setup_code = autocrypt::generate_setup_code();
+    match autocrypt::setup_message(&cert, &setup_code) {
+        Ok(payload) => Ok(OperationResult::ok(AutocryptSetupMessage {
+            setup_code,
+            message: autocrypt::setup_message_html(&payload),
+        })),
+        Err(e) => Ok(OperationResult::err(format!("Failed to create setup message: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn import_autocrypt_setup_message(
+    message: String,
+    setup_code: String,
+    passphrase: String, // Protects the imported key in the vault; empty leaves it unprotected
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyMetadata>, String> {
+    log::info!("IMPORT_AUTOCRYPT_SETUP_MESSAGE command called");
+
+    // 1. Decrypt the transferred key
+    let cert = match autocrypt::import_setup_message(&message, &setup_code) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to decrypt setup message: {}", e))),
+    };
+
+    // 2. Protect it like every other key in the vault
+    let cert = if passphrase.is_empty() {
+        cert
+    } else {
+        match crate::core::crypto::change_passphrase(cert, "", &passphrase) {
+            Ok(c) => c,
+            Err(e) => return Ok(OperationResult::err(format!("Failed to protect key: {}", e))),
+        }
+    };
+
+    let fingerprint = cert.fingerprint().to_hex();
+    if let Err(e) = keyring::import_cert(&vault, cert) {
+        return Ok(OperationResult::err(format!("Failed to import key: {}", e)));
+    }
+
+    match keyring::load_key_metadata(&vault, &fingerprint) {
+        Ok(metadata) => Ok(OperationResult::ok(metadata)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to load key metadata: {}", e))),
+    }
+}
@@ -0,0 +1,106 @@
+// Keyserver lookups and uploads, only available when enabled in the settings
+
+use crate::core::{config, keyring, keyserver::Keyserver, storage::Vault};
+use crate::models::{ImportReport, KeyserverKey, KeyserverUpload, OperationResult};
+use tauri::State;
+
+// Re-reads the config so toggling the setting takes effect immediately.
+fn connect() -> Result<Keyserver, String> {
+    let config = config::load_config().map_err(|e| format!("Failed to load config: {}", e))?;
+    Keyserver::from_config(&config.keyserver).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn keyserver_search(query: String) -> Result<OperationResult<Vec<KeyserverKey>>, String> {
+    log::info!("KEYSERVER_SEARCH command called for query: {}", query);
+
+    let server = match connect() {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e)),
+    };
+
+    match server.search(&query).await {
+        Ok(keys) => Ok(OperationResult::ok(keys)),
+        Err(e) => {
+            log::error!("Keyserver search failed: {}", e);
+            Ok(OperationResult::err(format!("Keyserver search failed: {}", e)))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn keyserver_fetch(
+    query: String, // Usually a fingerprint from the search results
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<ImportReport>, String> {
+    log::info!("KEYSERVER_FETCH command called for query: {}", query);
+
+    let server = match connect() {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e)),
+    };
+
+    let fetched = match server.fetch(&query).await {
+        Ok(f) => f,
+        Err(e) => {
+            log::error!("Keyserver fetch failed: {}", e);
+            return Ok(OperationResult::err(format!("Keyserver fetch failed: {}", e)));
+        }
+    };
+
+    // Same merge as import_key; keys that didn't match the query are
+    // reported as failed.
+    match keyring::import_certs(&vault, fetched.certs) {
+        Ok(mut report) => {
+            for entry in fetched.rejected {
+                report.push(entry);
+            }
+            log::info!(
+                "Imported from keyserver: {} new, {} updated, {} unchanged, {} failed",
+                report.new, report.updated, report.unchanged, report.failed
+            );
+            Ok(OperationResult::ok(report))
+        }
+        Err(e) => Ok(OperationResult::err(format!("Failed to import keys: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn keyserver_upload(
+    fingerprint: String,
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<KeyserverUpload>, String> {
+    log::info!("KEYSERVER_UPLOAD command called for fingerprint: {}", fingerprint);
+
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+
+    let server = match connect() {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e)),
+    };
+
+    let key_content = match keyring::load_key_content(&vault, &fingerprint) {
+        Ok(s) => s,
+        Err(e) => return Ok(OperationResult::err(e.to_string())),
+    };
+
+    let cert = match openpgp::Cert::from_bytes(key_content.as_bytes()) {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to parse key: {}", e))),
+    };
+
+    // Public parts only, without local certifications
+    let armored = match crate::core::crypto::serialize_public_keyring(&[cert], true) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+        Err(e) => return Ok(OperationResult::err(format!("Failed to export key: {}", e))),
+    };
+
+    match server.upload(&fingerprint, &armored).await {
+        Ok(upload) => Ok(OperationResult::ok(upload)),
+        Err(e) => {
+            log::error!("Keyserver upload failed: {}", e);
+            Ok(OperationResult::err(format!("Keyserver upload failed: {}", e)))
+        }
+    }
+}
@@ -2,4 +2,7 @@
 
 pub mod crypto;
 pub mod keys;
+pub mod keyserver;
+pub mod notifications;
+pub mod operations;
 pub mod settings;
@@ -0,0 +1,61 @@
+// Background checks that notify the frontend, currently expiring keys
+
+use crate::core::{config, expiry, storage::Vault};
+use crate::models::{ExpiringKey, ExpiryNotice, OperationResult};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Event emitted when keys expire within the configured window. The frontend
+/// offers to extend them through `extend_key_expiry`.
+pub const EXPIRY_EVENT: &str = "keys-expiring";
+
+// Gives the frontend time to register its listener before the first check.
+const STARTUP_DELAY: Duration = Duration::from_secs(10);
+
+/// Checks for expiring keys at startup and then every
+/// `expiry_check_interval_hours`, re-reading the config each round.
+pub fn start_expiry_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            let config = config::load_config().unwrap_or_else(|e| {
+                log::error!("Expiry watcher failed to load config: {}", e);
+                config::AppConfig::default()
+            });
+
+            let vault = app.state::<Vault>();
+            match expiry::expiring_keys(&vault, config.expiry_warning_days, config.expiry_warn_favorite_public_keys) {
+                Ok(keys) if !keys.is_empty() => {
+                    log::info!("{} keys expire within {} days", keys.len(), config.expiry_warning_days);
+                    let notice = ExpiryNotice {
+                        window_days: config.expiry_warning_days,
+                        keys,
+                    };
+                    if let Err(e) = app.emit(EXPIRY_EVENT, notice) {
+                        log::error!("Failed to emit expiry notice: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => log::error!("Failed to check for expiring keys: {}", e),
+            }
+
+            let hours = config.expiry_check_interval_hours.max(1);
+            tokio::time::sleep(Duration::from_secs(hours.saturating_mul(60 * 60))).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn check_expiring_keys(
+    window_days: Option<u32>, // Defaults to the configured window
+    vault: State<'_, Vault>,
+) -> Result<OperationResult<Vec<ExpiringKey>>, String> {
+    log::info!("CHECK_EXPIRING_KEYS command called");
+    let config = config::load_config().map_err(|e| e.to_string())?;
+    let window_days = window_days.unwrap_or(config.expiry_warning_days);
+
+    match expiry::expiring_keys(&vault, window_days, config.expiry_warn_favorite_public_keys) {
+        Ok(keys) => Ok(OperationResult::ok(keys)),
+        Err(e) => Ok(OperationResult::err(format!("Failed to check for expiring keys: {}", e))),
+    }
+}
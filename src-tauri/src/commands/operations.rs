@@ -0,0 +1,157 @@
+// Long-running operations: blocking thread pool, progress events and cancellation
+
+use crate::core::storage::Vault;
+use crate::models::{OperationProgress, OperationResult, OperationStage};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Event emitted for every stage change of a running operation.
+pub const PROGRESS_EVENT: &str = "operation-progress";
+
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Cancellation flags of all running operations, keyed by operation ID.
+#[derive(Default)]
+pub struct Operations {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+/// Handed to the work closure to report progress and poll for cancellation.
+pub struct OperationContext {
+    pub id: String,
+    kind: String,
+    app: AppHandle,
+    cancelled: Arc<AtomicBool>,
+    stopped: AtomicBool, // Set once the work gave up because of `cancelled`
+}
+
+impl OperationContext {
+    pub fn progress(&self, percent: u8, message: &str) {
+        self.emit(OperationStage::Progress, Some(percent), message);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// The result to return when the work stops because `is_cancelled()`
+    /// returned true. Only operations that end this way count as cancelled.
+    pub fn cancelled<T>(&self) -> OperationResult<T> {
+        self.stopped.store(true, Ordering::SeqCst);
+        OperationResult::err("Operation cancelled")
+    }
+
+    fn emit(&self, stage: OperationStage, percent: Option<u8>, message: &str) {
+        let payload = OperationProgress {
+            operation_id: self.id.clone(),
+            kind: self.kind.clone(),
+            stage,
+            percent,
+            message: message.to_string(),
+        };
+        if let Err(e) = self.app.emit(PROGRESS_EVENT, payload) {
+            log::warn!("Failed to emit progress for operation {}: {}", self.id, e);
+        }
+    }
+}
+
+/// Runs `work` on the blocking thread pool so CPU-heavy crypto never stalls
+/// the async runtime, and reports started/finished/failed/cancelled events.
+///
+/// Cancellation is cooperative: `work` checks `is_cancelled()` between steps,
+/// last right before its commit point, and returns `ctx.cancelled()` if it
+/// stops. A cancel request arriving after that point does not change the
+/// result. A caller-supplied `operation_id` must not already be running.
+pub async fn run_operation<T, F>(
+    app: AppHandle,
+    operation_id: Option<String>,
+    kind: &str,
+    work: F,
+) -> Result<OperationResult<T>, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&Vault, &OperationContext) -> OperationResult<T> + Send + 'static,
+{
+    let id = operation_id
+        .unwrap_or_else(|| format!("op-{}", NEXT_OPERATION_ID.fetch_add(1, Ordering::SeqCst)));
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    match app.state::<Operations>().active.lock().unwrap().entry(id.clone()) {
+        Entry::Occupied(_) => {
+            return Ok(OperationResult::err(format!("Operation {} is already running", id)));
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(cancelled.clone());
+        }
+    }
+
+    let ctx = OperationContext {
+        id: id.clone(),
+        kind: kind.to_string(),
+        app: app.clone(),
+        cancelled,
+        stopped: AtomicBool::new(false),
+    };
+    ctx.emit(OperationStage::Started, Some(0), "");
+
+    let worker_app = app.clone();
+    let joined = tauri::async_runtime::spawn_blocking(move || {
+        let vault = worker_app.state::<Vault>();
+        let result = work(&vault, &ctx);
+
+        match &result.error {
+            Some(_) if ctx.stopped.load(Ordering::SeqCst) => {
+                ctx.emit(OperationStage::Cancelled, None, "Operation cancelled")
+            }
+            Some(e) => ctx.emit(OperationStage::Failed, None, e),
+            None => ctx.emit(OperationStage::Finished, Some(100), ""),
+        }
+        result
+    })
+    .await;
+
+    app.state::<Operations>().active.lock().unwrap().remove(&id);
+
+    joined.map_err(|e| format!("Operation {} failed: {}", id, e))
+}
+
+/// Copies `reader` to `writer`, checking for cancellation between chunks.
+/// Returns false if the copy stopped early because of it.
+pub fn copy_unless_cancelled<R: Read, W: Write>(
+    ctx: &OperationContext,
+    reader: &mut R,
+    writer: &mut W,
+) -> std::io::Result<bool> {
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        if ctx.is_cancelled() {
+            return Ok(false);
+        }
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(true),
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buffer[..n])?;
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_operation(
+    operation_id: String,
+    operations: State<'_, Operations>,
+) -> Result<OperationResult<bool>, String> {
+    log::info!("CANCEL_OPERATION command called for operation: {}", operation_id);
+    match operations.active.lock().unwrap().get(&operation_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            Ok(OperationResult::ok(true))
+        }
+        None => Ok(OperationResult::ok(false)),
+    }
+}
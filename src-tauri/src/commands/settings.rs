@@ -1,5 +1,5 @@
 use tauri::State;
-use crate::core::{config, storage::Vault};
+use crate::core::{config, keyserver::Keyserver, storage::Vault};
 use crate::models::OperationResult;
 use std::path::PathBuf;
 
@@ -118,3 +118,34 @@ pub async fn restore_db(
         Err(e) => Ok(OperationResult::err(format!("Failed to restore database: {}", e))),
     }
 }
+
+#[tauri::command]
+pub async fn get_keyserver_config() -> Result<OperationResult<config::KeyserverConfig>, String> {
+    let config = config::load_config().map_err(|e| e.to_string())?;
+    Ok(OperationResult::ok(config.keyserver))
+}
+
+#[tauri::command]
+pub async fn set_keyserver_config(
+    keyserver: config::KeyserverConfig,
+) -> Result<OperationResult<bool>, String> {
+    log::info!(
+        "SET_KEYSERVER_CONFIG command called, enabled: {}, url: {:?}",
+        keyserver.enabled, keyserver.url
+    );
+
+    // Reject URLs the client could not use before they are saved
+    if keyserver.enabled {
+        if let Err(e) = Keyserver::from_config(&keyserver) {
+            return Ok(OperationResult::err(e.to_string()));
+        }
+    }
+
+    let mut config = match config::load_config() {
+        Ok(c) => c,
+        Err(e) => return Ok(OperationResult::err(format!("Failed to load config: {}", e))),
+    };
+    config.keyserver = keyserver;
+    config::save_config(&config).map_err(|e| e.to_string())?;
+    Ok(OperationResult::ok(true))
+}
@@ -0,0 +1,224 @@
+// Keyring health audit
+use crate::core::storage::Vault;
+use crate::models::{AuditFinding, AuditFindingKind, AuditReport, AuditSeverity};
+use anyhow::Result;
+use sequoia_openpgp as openpgp;
+use openpgp::cert::prelude::*;
+use openpgp::policy::StandardPolicy;
+use openpgp::types::{HashAlgorithm, KeyFlags, PublicKeyAlgorithm, RevocationStatus};
+use openpgp::Cert;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+const MIN_RSA_BITS: usize = 2048;
+
+/// Audits every cert in the vault.
+pub fn audit_keyring(vault: &Vault, expiring_within_days: u32) -> Result<AuditReport> {
+    let certs = crate::core::trust::load_vault_certs(vault)?;
+    let now = SystemTime::now();
+
+    Ok(AuditReport {
+        checked_at: chrono::DateTime::<chrono::Utc>::from(now).to_rfc3339(),
+        keys_checked: certs.len(),
+        findings: audit_certs(&certs, expiring_within_days, now),
+    })
+}
+
+/// Runs all checks on `certs` as of `now`, most severe findings first.
+pub fn audit_certs(certs: &[Cert], expiring_within_days: u32, now: SystemTime) -> Vec<AuditFinding> {
+    let mut findings = Vec::new();
+    for cert in certs {
+        audit_cert(cert, expiring_within_days, now, &mut findings);
+    }
+    duplicate_user_ids(certs, &mut findings);
+
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.fingerprint.cmp(&b.fingerprint)));
+    findings
+}
+
+fn audit_cert(cert: &Cert, expiring_within_days: u32, now: SystemTime, findings: &mut Vec<AuditFinding>) {
+    let p = StandardPolicy::new();
+    let fingerprint = cert.fingerprint().to_hex();
+    let user_id = display_user_id(cert);
+
+    let mut push = |component: Option<String>, kind, severity, message: String| {
+        findings.push(AuditFinding {
+            fingerprint: fingerprint.clone(),
+            user_id: user_id.clone(),
+            component,
+            kind,
+            severity,
+            message,
+        })
+    };
+
+    // 1. Weak algorithms, on the primary key and every subkey
+    for ka in cert.keys() {
+        let key = ka.key();
+        let component = (!ka.primary()).then(|| key.fingerprint().to_hex());
+        let which = if ka.primary() { "Primary key" } else { "Subkey" };
+
+        #[allow(deprecated)]
+        match key.pk_algo() {
+            PublicKeyAlgorithm::RSAEncryptSign | PublicKeyAlgorithm::RSASign | PublicKeyAlgorithm::RSAEncrypt => {
+                if let Some(bits) = key.mpis().bits().filter(|b| *b < MIN_RSA_BITS) {
+                    push(
+                        component,
+                        AuditFindingKind::WeakRsaKey,
+                        AuditSeverity::High,
+                        format!("{} is RSA {} bits, below the {} bit minimum", which, bits, MIN_RSA_BITS),
+                    );
+                }
+            }
+            PublicKeyAlgorithm::DSA => push(
+                component,
+                AuditFindingKind::DsaKey,
+                AuditSeverity::Medium,
+                format!("{} uses DSA, which is deprecated", which),
+            ),
+            _ => {}
+        }
+    }
+
+    // 2. SHA-1 on the newest binding signature of each user ID and subkey
+    for ua in cert.userids() {
+        if ua.self_signatures().next().map(|s| s.hash_algo()) == Some(HashAlgorithm::SHA1) {
+            let uid = String::from_utf8_lossy(ua.userid().value()).to_string();
+            push(
+                Some(uid.clone()),
+                AuditFindingKind::Sha1Binding,
+                AuditSeverity::High,
+                format!("Self-signature on user ID \"{}\" uses SHA-1", uid),
+            );
+        }
+    }
+    for ka in cert.keys().subkeys() {
+        if ka.self_signatures().next().map(|s| s.hash_algo()) == Some(HashAlgorithm::SHA1) {
+            push(
+                Some(ka.key().fingerprint().to_hex()),
+                AuditFindingKind::Sha1Binding,
+                AuditSeverity::High,
+                "Subkey binding signature uses SHA-1".to_string(),
+            );
+        }
+    }
+
+    // 3. Unprotected secret key material
+    for protection in crate::core::crypto::protection_status(cert) {
+        if !protection.encrypted {
+            let which = if protection.is_primary { "Primary secret key" } else { "Secret subkey" };
+            push(
+                (!protection.is_primary).then(|| protection.fingerprint.clone()),
+                AuditFindingKind::UnprotectedSecretKey,
+                AuditSeverity::High,
+                format!("{} is stored without a passphrase", which),
+            );
+        }
+    }
+
+    // 4. Everything below needs the cert to be valid under the standard policy.
+    let vc = match cert.with_policy(&p, Some(now)) {
+        Ok(vc) => vc,
+        Err(e) => {
+            push(
+                None,
+                AuditFindingKind::PolicyViolation,
+                AuditSeverity::Critical,
+                format!("Key is rejected by the standard policy: {}", e),
+            );
+            return;
+        }
+    };
+
+    let valid_subkeys: Vec<_> = vc.keys().subkeys().map(|ka| ka.key().fingerprint()).collect();
+    for ka in cert.keys().subkeys() {
+        if !valid_subkeys.contains(&ka.key().fingerprint()) {
+            push(
+                Some(ka.key().fingerprint().to_hex()),
+                AuditFindingKind::PolicyViolation,
+                AuditSeverity::Medium,
+                "Subkey has no binding signature accepted by the standard policy".to_string(),
+            );
+        }
+    }
+
+    // Revoked keys are not expected to be usable any more.
+    if let RevocationStatus::Revoked(_) = vc.revocation_status() {
+        return;
+    }
+
+    // 5. Expiry of the primary key
+    if let Some(expires) = vc.primary_key().key_expiration_time() {
+        let date = chrono::DateTime::<chrono::Utc>::from(expires).format("%Y-%m-%d");
+        if expires <= now {
+            push(
+                None,
+                AuditFindingKind::Expired,
+                AuditSeverity::Medium,
+                format!("Key expired on {}", date),
+            );
+            return;
+        }
+        let window = Duration::from_secs(expiring_within_days as u64 * 24 * 60 * 60);
+        if expires <= now + window {
+            push(
+                None,
+                AuditFindingKind::Expiring,
+                AuditSeverity::Low,
+                format!("Key expires on {}", date),
+            );
+        }
+    }
+
+    // 6. Usable encryption subkey
+    let encryption = KeyFlags::empty().set_transport_encryption().set_storage_encryption();
+    if vc.keys().supported().alive().revoked(false).key_flags(encryption).next().is_none() {
+        push(
+            None,
+            AuditFindingKind::NoEncryptionSubkey,
+            AuditSeverity::Medium,
+            "Key has no valid encryption subkey".to_string(),
+        );
+    }
+}
+
+// The same user ID on several certs usually means stale or impostor keys.
+fn duplicate_user_ids(certs: &[Cert], findings: &mut Vec<AuditFinding>) {
+    let mut owners: BTreeMap<String, Vec<&Cert>> = BTreeMap::new();
+    for cert in certs {
+        for ua in cert.userids() {
+            let uid = String::from_utf8_lossy(ua.userid().value()).trim().to_string();
+            let entry = owners.entry(uid).or_default();
+            if !entry.iter().any(|c| c.fingerprint() == cert.fingerprint()) {
+                entry.push(cert);
+            }
+        }
+    }
+
+    for (uid, certs) in owners.into_iter().filter(|(_, certs)| certs.len() > 1) {
+        for cert in &certs {
+            let others: Vec<String> = certs
+                .iter()
+                .filter(|c| c.fingerprint() != cert.fingerprint())
+                .map(|c| c.fingerprint().to_hex())
+                .collect();
+            findings.push(AuditFinding {
+                fingerprint: cert.fingerprint().to_hex(),
+                user_id: display_user_id(cert),
+                component: Some(uid.clone()),
+                kind: AuditFindingKind::DuplicateUserId,
+                severity: AuditSeverity::Low,
+                message: format!("User ID \"{}\" also appears on {}", uid, others.join(", ")),
+            });
+        }
+    }
+}
+
+fn display_user_id(cert: &Cert) -> Option<String> {
+    let p = StandardPolicy::new();
+    cert.with_policy(&p, None)
+        .ok()
+        .and_then(|vc| vc.primary_userid().ok().map(|ua| ua.userid().clone()))
+        .or_else(|| cert.userids().next().map(|ua| ua.userid().clone()))
+        .map(|uid| String::from_utf8_lossy(uid.value()).to_string())
+}
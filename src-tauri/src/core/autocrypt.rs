@@ -0,0 +1,365 @@
+// Autocrypt (Level 1) headers and Setup Messages
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use sequoia_openpgp as openpgp;
+use openpgp::crypto::{Password, SessionKey};
+use openpgp::packet::{PKESK, SKESK};
+use openpgp::parse::stream::{
+    DecryptionHelper, DecryptorBuilder, MessageStructure, VerificationHelper,
+};
+use openpgp::parse::Parse;
+use openpgp::policy::StandardPolicy;
+use openpgp::serialize::stream::{Armorer, Encryptor2, LiteralWriter, Message};
+use openpgp::types::SymmetricAlgorithm;
+use openpgp::{Cert, Fingerprint, KeyHandle};
+use std::io::{Read, Write};
+
+/// A parsed `Autocrypt` or `Autocrypt-Gossip` header.
+pub struct AutocryptHeader {
+    pub addr: String,
+    pub keydata: Vec<u8>,
+}
+
+/// Reduces `cert` to what an Autocrypt header carries: the primary key, the
+/// user ID for `addr` and one encryption subkey, each with its current
+/// binding signature.
+pub fn autocrypt_cert(cert: &Cert, addr: &str) -> Result<Cert> {
+    let p = StandardPolicy::new();
+    let vc = cert.with_policy(&p, None)?;
+
+    let userid = vc
+        .userids()
+        .find(|ua| matches!(ua.userid().email2(), Ok(Some(e)) if e.eq_ignore_ascii_case(addr)))
+        .map(|ua| ua.userid().clone())
+        .ok_or_else(|| anyhow!("Key has no valid user ID for {}", addr))?;
+
+    let subkey = vc
+        .keys()
+        .subkeys()
+        .alive()
+        .revoked(false)
+        .for_transport_encryption()
+        .max_by_key(|ka| ka.key().creation_time())
+        .map(|ka| ka.key().fingerprint())
+        .ok_or_else(|| anyhow!("Key has no valid encryption subkey"))?;
+
+    Ok(crate::core::crypto::minimize_cert(cert)?
+        .retain_userids(|ua| ua.userid() == &userid)
+        .retain_subkeys(|ka| ka.key().fingerprint() == subkey))
+}
+
+/// Builds an `Autocrypt:` (or `Autocrypt-Gossip:`) header line for `addr`,
+/// folded for use in mail headers.
+pub fn header(cert: &Cert, addr: &str, prefer_encrypt_mutual: bool, gossip: bool) -> Result<String> {
+    let minimal = autocrypt_cert(cert, addr)?;
+    let keydata = BASE64.encode(crate::core::crypto::serialize_public_keyring(&[minimal], false)?);
+
+    let mut out = if gossip {
+        format!("Autocrypt-Gossip: addr={};", addr)
+    } else {
+        format!("Autocrypt: addr={};", addr)
+    };
+    if prefer_encrypt_mutual && !gossip {
+        out.push_str(" prefer-encrypt=mutual;");
+    }
+    out.push_str(" keydata=");
+    for chunk in keydata.as_bytes().chunks(72) {
+        out.push_str("\r\n ");
+        out.push_str(&String::from_utf8_lossy(chunk));
+    }
+    Ok(out)
+}
+
+/// Finds every Autocrypt and Autocrypt-Gossip header in `text`, which may be
+/// a whole pasted mail or a bare header value. Folded lines are joined first.
+pub fn parse_headers(text: &str) -> Vec<Result<AutocryptHeader>> {
+    let mut unfolded: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match unfolded.last_mut() {
+            Some(last) if line.starts_with(' ') || line.starts_with('\t') => last.push_str(line.trim()),
+            _ => unfolded.push(line.trim_end().to_string()),
+        }
+    }
+
+    unfolded
+        .iter()
+        .filter_map(|line| {
+            let lower = line.to_ascii_lowercase();
+            if lower.starts_with("autocrypt-gossip:") {
+                Some(&line["autocrypt-gossip:".len()..])
+            } else if lower.starts_with("autocrypt:") {
+                Some(&line["autocrypt:".len()..])
+            } else if lower.starts_with("addr=") {
+                Some(line.as_str())
+            } else {
+                None
+            }
+        })
+        .map(parse_header_value)
+        .collect()
+}
+
+fn parse_header_value(value: &str) -> Result<AutocryptHeader> {
+    let mut addr = None;
+    let mut keydata = None;
+
+    for attribute in value.split(';').map(str::trim).filter(|a| !a.is_empty()) {
+        let (name, value) = attribute
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Malformed Autocrypt attribute: {}", attribute))?;
+        match name.trim() {
+            "addr" => addr = Some(value.trim().to_string()),
+            "keydata" => {
+                let cleaned: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+                keydata = Some(BASE64.decode(cleaned)?);
+            }
+            // Only matters to mail clients choosing whether to encrypt.
+            "prefer-encrypt" => {}
+            // Non-critical attributes start with an underscore; unknown
+            // critical ones invalidate the header.
+            name if name.starts_with('_') => {}
+            name => return Err(anyhow!("Unknown critical Autocrypt attribute: {}", name)),
+        }
+    }
+
+    Ok(AutocryptHeader {
+        addr: addr.ok_or_else(|| anyhow!("Autocrypt header has no addr attribute"))?,
+        keydata: keydata.ok_or_else(|| anyhow!("Autocrypt header has no keydata attribute"))?,
+    })
+}
+
+/// Parses the key of `header` and checks it belongs to the header's address.
+pub fn header_cert(header: &AutocryptHeader) -> Result<Cert> {
+    let cert = Cert::from_bytes(&header.keydata)?;
+    let matches = cert
+        .userids()
+        .any(|ua| matches!(ua.userid().email2(), Ok(Some(e)) if e.eq_ignore_ascii_case(&header.addr)));
+    if !matches {
+        return Err(anyhow!("Key {} has no user ID for {}", cert.fingerprint(), header.addr));
+    }
+    Ok(cert.strip_secret_key_material())
+}
+
+/// Generates a setup code: 36 random digits in nine blocks of four.
+pub fn generate_setup_code() -> String {
+    let mut digits = String::with_capacity(36);
+    let mut byte = [0u8; 1];
+    while digits.len() < 36 {
+        openpgp::crypto::random(&mut byte);
+        // Rejection sampling keeps every digit equally likely.
+        if byte[0] < 250 {
+            digits.push((b'0' + byte[0] % 10) as char);
+        }
+    }
+    blocks(&digits)
+}
+
+// The code is typed by hand; only its digits matter.
+fn normalize_setup_code(code: &str) -> Result<String> {
+    let digits: String = code.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.len() != 36 {
+        return Err(anyhow!("The setup code must have 36 digits"));
+    }
+    Ok(blocks(&digits))
+}
+
+fn blocks(digits: &str) -> String {
+    digits
+        .as_bytes()
+        .chunks(4)
+        .map(|c| String::from_utf8_lossy(c).to_string())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Builds the armored Autocrypt Setup Message payload: the armored,
+/// unprotected TSK encrypted with `setup_code` as the password.
+pub fn setup_message(tsk: &Cert, setup_code: &str) -> Result<String> {
+    let setup_code = normalize_setup_code(setup_code)?;
+
+    let mut armored_key = Vec::new();
+    {
+        let mut writer = openpgp::armor::Writer::with_headers(
+            &mut armored_key,
+            openpgp::armor::Kind::SecretKey,
+            vec![("Autocrypt-Prefer-Encrypt", "mutual")],
+        )?;
+        openpgp::serialize::Serialize::serialize(&tsk.as_tsk(), &mut writer)?;
+        writer.finalize()?;
+    }
+
+    let mut out = Vec::new();
+    let message = Message::new(&mut out);
+    let message = Armorer::new(message)
+        .kind(openpgp::armor::Kind::Message)
+        .add_header("Passphrase-Format", "numeric9x4")
+        .add_header("Passphrase-Begin", &setup_code[..2])
+        .build()?;
+    let message = Encryptor2::with_passwords(message, Some(Password::from(setup_code.as_str())))
+        .symmetric_algo(SymmetricAlgorithm::AES128)
+        .build()?;
+    let mut writer = LiteralWriter::new(message).build()?;
+    writer.write_all(&armored_key)?;
+    writer.finalize()?;
+
+    Ok(String::from_utf8(out)?)
+}
+
+/// Wraps the setup message payload in the HTML attachment described by the
+/// Autocrypt specification.
+pub fn setup_message_html(payload: &str) -> String {
+    format!(
+        "<html><body>\n\
+         <p>This message contains all information to transfer your Autocrypt\n\
+         settings along with your secret key securely from your original\n\
+         device.</p>\n\
+         <p>To set up your new device for Autocrypt, please follow the\n\
+         instructions that should be presented by your new device.</p>\n\
+         <p>You can keep this message and use it as a backup for your secret\n\
+         key. If you want to do this, you should write down the Setup Code\n\
+         and store it securely.</p>\n\
+         <pre>\n{}</pre>\n\
+         </body></html>\n",
+        payload
+    )
+}
+
+/// Decrypts a setup message (the armored payload, or the HTML attachment
+/// holding it) and returns the transferred TSK.
+pub fn import_setup_message(message: &str, setup_code: &str) -> Result<Cert> {
+    let setup_code = normalize_setup_code(setup_code)?;
+
+    let start = message
+        .find("-----BEGIN PGP MESSAGE-----")
+        .ok_or_else(|| anyhow!("No Autocrypt Setup Message found"))?;
+    let end = message[start..]
+        .find("-----END PGP MESSAGE-----")
+        .map(|i| start + i + "-----END PGP MESSAGE-----".len())
+        .ok_or_else(|| anyhow!("Autocrypt Setup Message is truncated"))?;
+
+    struct Helper {
+        password: Password,
+    }
+
+    impl VerificationHelper for Helper {
+        fn get_certs(&mut self, _ids: &[KeyHandle]) -> openpgp::Result<Vec<Cert>> {
+            Ok(Vec::new())
+        }
+        fn check(&mut self, _structure: MessageStructure) -> openpgp::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl DecryptionHelper for Helper {
+        fn decrypt<D>(
+            &mut self,
+            _pkesks: &[PKESK],
+            skesks: &[SKESK],
+            _sym_algo: Option<SymmetricAlgorithm>,
+            mut decrypt: D,
+        ) -> openpgp::Result<Option<Fingerprint>>
+        where
+            D: FnMut(SymmetricAlgorithm, &SessionKey) -> bool,
+        {
+            for skesk in skesks {
+                if let Ok((algo, session_key)) = skesk.decrypt(&self.password) {
+                    if decrypt(algo, &session_key) {
+                        return Ok(None);
+                    }
+                }
+            }
+            Err(anyhow!("Wrong setup code"))
+        }
+    }
+
+    let p = StandardPolicy::new();
+    let helper = Helper {
+        password: Password::from(setup_code.as_str()),
+    };
+    let mut decryptor = DecryptorBuilder::from_bytes(&message.as_bytes()[start..end])?
+        .with_policy(&p, None, helper)?;
+
+    let mut armored_key = Vec::new();
+    decryptor.read_to_end(&mut armored_key)?;
+
+    let cert = Cert::from_bytes(&armored_key)?;
+    if !cert.is_tsk() {
+        return Err(anyhow!("The setup message holds no secret key"));
+    }
+    Ok(cert)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openpgp::cert::CertBuilder;
+
+    fn alice() -> Cert {
+        CertBuilder::general_purpose(None, Some("Alice <alice@example.org>"))
+            .generate()
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let cert = alice();
+        let line = header(&cert, "alice@example.org", true, false).unwrap();
+
+        let mut lines = line.split("\r\n");
+        assert_eq!(lines.next(), Some("Autocrypt: addr=alice@example.org; prefer-encrypt=mutual; keydata="));
+        for folded in lines {
+            assert!(folded.starts_with(' '));
+            assert!(folded.len() <= 73);
+        }
+
+        // As pasted from a mail, between other headers.
+        let mail = format!("From: Alice <alice@example.org>\r\n{}\r\nSubject: Hi\r\n", line);
+        let headers = parse_headers(&mail);
+        assert_eq!(headers.len(), 1);
+        let parsed = headers.into_iter().next().unwrap().unwrap();
+        assert_eq!(parsed.addr, "alice@example.org");
+        let imported = header_cert(&parsed).unwrap();
+        assert_eq!(imported.fingerprint(), cert.fingerprint());
+        assert!(!imported.is_tsk());
+        assert_eq!(imported.userids().count(), 1);
+        assert_eq!(imported.keys().subkeys().count(), 1);
+
+        let gossip = header(&cert, "alice@example.org", true, true).unwrap();
+        assert!(gossip.starts_with("Autocrypt-Gossip: addr=alice@example.org; keydata="));
+        assert!(parse_headers(&gossip)[0].is_ok());
+
+        // The key must carry the header's address.
+        let other = parse_headers(&line.replace("alice@example.org", "mallory@example.org"));
+        assert!(header_cert(other[0].as_ref().unwrap()).is_err());
+    }
+
+    #[test]
+    fn critical_attributes() {
+        let headers = parse_headers("Autocrypt: addr=a@example.org; _comment=hi; keydata=AAAA");
+        assert!(headers[0].is_ok());
+        let headers = parse_headers("Autocrypt: addr=a@example.org; unknown=1; keydata=AAAA");
+        assert!(headers[0].is_err());
+        let headers = parse_headers("Autocrypt: keydata=AAAA");
+        assert!(headers[0].is_err());
+    }
+
+    #[test]
+    fn setup_message_round_trip() {
+        let cert = alice();
+        let code = generate_setup_code();
+        assert_eq!(code.len(), 44);
+        assert_eq!(code.split('-').count(), 9);
+
+        let html = setup_message_html(&setup_message(&cert, &code).unwrap());
+        let imported = import_setup_message(&html, &code.replace('-', "")).unwrap();
+        assert_eq!(imported, cert);
+        assert!(imported.is_tsk());
+
+        let first = if code.starts_with('0') { "1" } else { "0" };
+        let wrong = format!("{}{}", first, &code[1..]);
+        assert!(import_setup_message(&html, &wrong).is_err());
+        assert!(import_setup_message(&html, "1234").is_err());
+    }
+}
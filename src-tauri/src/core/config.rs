@@ -9,17 +9,56 @@ pub struct AppConfig {
     pub db_path: Option<String>,
     #[serde(default = "default_first_run")]
     pub first_run: bool,
+    #[serde(default = "default_expiry_warning_days")]
+    pub expiry_warning_days: u32,
+    #[serde(default = "default_expiry_check_interval_hours")]
+    pub expiry_check_interval_hours: u64,
+    #[serde(default)]
+    pub expiry_warn_favorite_public_keys: bool, // Also warn about public keys marked as favorite
+    #[serde(default)]
+    pub keyserver: KeyserverConfig,
+}
+
+/// Keyserver access. Armor works offline by default; nothing is sent to or
+/// fetched from a keyserver unless it is enabled here and the user asks.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct KeyserverConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    pub url: Option<String>, // e.g. "hkps://keys.example.org" or "http://localhost:11371"
+    #[serde(default)]
+    pub protocol: KeyserverProtocol,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyserverProtocol {
+    #[default]
+    Hkp, // `/pks/lookup` and `/pks/add`
+    Vks, // The verifying keyserver API under `/vks/v1/`
 }
 
 fn default_first_run() -> bool {
     true
 }
 
+fn default_expiry_warning_days() -> u32 {
+    30
+}
+
+fn default_expiry_check_interval_hours() -> u64 {
+    24
+}
+
 impl AppConfig {
     pub fn default() -> Self {
         Self {
             db_path: None,
             first_run: true,
+            expiry_warning_days: default_expiry_warning_days(),
+            expiry_check_interval_hours: default_expiry_check_interval_hours(),
+            expiry_warn_favorite_public_keys: false,
+            keyserver: KeyserverConfig::default(),
         }
     }
 }
@@ -1,77 +1,126 @@
 // Cryptographic operations using Sequoia OpenPGP
 
+use crate::models::{KeyGenProfile, SecretKeyProtection, UserId};
 use anyhow::Result;
 use openpgp::cert::prelude::*;
-use openpgp::crypto::Password;
+use openpgp::crypto::{KeyPair, Password, S2K};
+use openpgp::packet::key::SecretKeyMaterial;
+use openpgp::packet::{key, Key};
 use openpgp::policy::StandardPolicy;
 use openpgp::serialize::stream::{Armorer, Message};
 use openpgp::serialize::Serialize; // Added Serialize trait
+use openpgp::packet::signature::SignatureBuilder;
+use openpgp::types::{KeyFlags, ReasonForRevocation, SignatureType};
 use sequoia_openpgp as openpgp;
 
-pub fn generate_keypair(
-    name: &str,
-    email: &str,
-    passphrase: &str,
-    valid_seconds: Option<u64>,
-    key_type: &str,
-) -> Result<(String, String)> {
-    println!("DEBUG: crypto::generate_keypair started with key_type={}", key_type);
+pub fn generate_keypair(profile: &KeyGenProfile, passphrase: &str) -> Result<(String, String)> {
+    log::debug!("generate_keypair started with key_type={}", profile.key_type);
     let _p = StandardPolicy::new();
 
-    let userid = format!("{} <{}>", name, email);
+    if profile.user_ids.is_empty() {
+        return Err(anyhow::anyhow!("At least one user ID is required"));
+    }
 
     // Map key_type string to Sequoia CipherSuite
-    let cipher_suite = match key_type {
-        "rsa2048" => openpgp::cert::CipherSuite::RSA2k,
-        "rsa4096" => openpgp::cert::CipherSuite::RSA4k,
-        _ => openpgp::cert::CipherSuite::Cv25519, // Ed25519 / Curve25519 (default)
-    };
+    let cipher_suite = cipher_suite_for(&profile.key_type);
 
     // 1. Generate a CertBuilder with the selected cipher suite
-    let mut builder = CertBuilder::new()
-        .add_userid(userid.as_str())
-        .set_cipher_suite(cipher_suite)
-        .add_signing_subkey()
-        .add_transport_encryption_subkey();
+    // The first user ID becomes the primary user ID.
+    let mut builder = CertBuilder::new().set_cipher_suite(cipher_suite);
+    for uid in &profile.user_ids {
+        builder = builder.add_userid(format_userid(uid).as_str());
+    }
 
-    if let Some(seconds) = valid_seconds {
-        // use std::time::Duration;
-        builder = builder.set_validity_period(std::time::Duration::from_secs(seconds));
+    if !profile.certify_only_primary {
+        builder = builder.set_primary_key_flags(KeyFlags::empty().set_certification().set_signing());
+    }
+
+    for subkey in &profile.subkeys {
+        let validity = subkey.expiry_timestamp.map(validity_until).transpose()?;
+        builder = builder.add_subkey(key_flags_for(&subkey.capability)?, validity, None);
+    }
+
+    if let Some(ts) = profile.expiry_timestamp {
+        builder = builder.set_validity_period(validity_until(ts)?);
     }
 
     let (mut cert, _) = builder.generate()?;
 
     // 2. Protect the secret keys with the passphrase
-    // Only encrypt if a passphrase is provided
+    // Only encrypt if a passphrase is provided. A failure here must abort
+    // generation instead of silently keeping unprotected key material.
     if !passphrase.is_empty() {
-        let password = Password::from(passphrase);
-        let packets: Vec<openpgp::packet::Packet> = cert.into();
-
-        // We need to map over packets because encrypt_secret consumes the key
-        let packets: Vec<openpgp::packet::Packet> = packets
-            .into_iter()
-            .map(|packet| match packet {
-                openpgp::packet::Packet::SecretKey(key) => {
-                    match key.clone().encrypt_secret(&password) {
-                        Ok(encrypted_key) => openpgp::packet::Packet::SecretKey(encrypted_key),
-                        Err(_) => openpgp::packet::Packet::SecretKey(key),
-                    }
-                }
-                openpgp::packet::Packet::SecretSubkey(key) => {
-                    match key.clone().encrypt_secret(&password) {
-                        Ok(encrypted_key) => openpgp::packet::Packet::SecretSubkey(encrypted_key),
-                        Err(_) => openpgp::packet::Packet::SecretSubkey(key),
-                    }
-                }
-                p => p,
-            })
-            .collect();
+        cert = change_passphrase(cert, "", passphrase)?;
 
-        // Reassemble the Cert
-        cert = Cert::try_from(packets)?;
+        if let Some(unprotected) = protection_status(&cert).iter().find(|k| !k.encrypted) {
+            return Err(anyhow::anyhow!(
+                "Secret key {} was not protected with the passphrase",
+                unprotected.fingerprint
+            ));
+        }
     }
 
     // 3. Export Public Key (Ascii Armored)
+    let public_key = armor_public(&cert)?;
+
+    // 4. Export Private Key (Ascii Armored)
+    let private_key = armor_tsk(&cert)?;
+
+    Ok((public_key, private_key))
+}
+
+/// Formats a user ID as "Name (Comment) <email>", leaving out empty parts.
+pub fn format_userid(uid: &UserId) -> String {
+    let mut parts = Vec::new();
+    if !uid.name.trim().is_empty() {
+        parts.push(uid.name.trim().to_string());
+    }
+    if let Some(comment) = uid.comment.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+        parts.push(format!("({})", comment));
+    }
+    if !uid.email.trim().is_empty() {
+        parts.push(format!("<{}>", uid.email.trim()));
+    }
+    parts.join(" ")
+}
+
+/// Turns an absolute expiry (Unix timestamp in seconds) into a validity period
+/// counted from now. Expiry dates in the past are rejected.
+pub fn validity_until(expiry_timestamp: i64) -> Result<std::time::Duration> {
+    let now = chrono::Utc::now().timestamp();
+    if expiry_timestamp <= now {
+        return Err(anyhow::anyhow!("Expiry date must be in the future"));
+    }
+    Ok(std::time::Duration::from_secs((expiry_timestamp - now) as u64))
+}
+
+/// Maps a capability name to key flags. `encrypt` covers both transport and
+/// storage encryption, like GnuPG's default encryption subkey.
+pub fn key_flags_for(capability: &str) -> Result<KeyFlags> {
+    match capability {
+        "sign" => Ok(KeyFlags::empty().set_signing()),
+        "encrypt" => Ok(KeyFlags::empty().set_transport_encryption().set_storage_encryption()),
+        "encrypt_transport" => Ok(KeyFlags::empty().set_transport_encryption()),
+        "encrypt_storage" => Ok(KeyFlags::empty().set_storage_encryption()),
+        "authenticate" => Ok(KeyFlags::empty().set_authentication()),
+        other => Err(anyhow::anyhow!("Unknown subkey capability: {}", other)),
+    }
+}
+
+/// Maps the key type string used by the frontend to a Sequoia cipher suite.
+pub fn cipher_suite_for(key_type: &str) -> CipherSuite {
+    match key_type {
+        "rsa2048" => CipherSuite::RSA2k,
+        "rsa3072" => CipherSuite::RSA3k,
+        "rsa4096" => CipherSuite::RSA4k,
+        "p256" => CipherSuite::P256,
+        "p384" => CipherSuite::P384,
+        _ => CipherSuite::Cv25519, // Ed25519 / Curve25519 (default)
+    }
+}
+
+/// Serializes the public parts of a cert as an ASCII armored public key block.
+pub fn armor_public(cert: &Cert) -> Result<String> {
     let mut public_bytes = Vec::new();
     {
         let message = Message::new(&mut public_bytes);
@@ -81,9 +130,12 @@ pub fn generate_keypair(
         cert.serialize(&mut armor_writer)?;
         armor_writer.finalize()?;
     }
-    let public_key = String::from_utf8(public_bytes)?;
+    Ok(String::from_utf8(public_bytes)?)
+}
 
-    // 4. Export Private Key (Ascii Armored)
+/// Serializes a cert including its secret key material as an ASCII armored
+/// private key block. This is the format stored in `keys.key_content`.
+pub fn armor_tsk(cert: &Cert) -> Result<String> {
     let mut private_bytes = Vec::new();
     {
         let message = Message::new(&mut private_bytes);
@@ -93,7 +145,383 @@ pub fn generate_keypair(
         cert.as_tsk().serialize(&mut armor_writer)?;
         armor_writer.finalize()?;
     }
-    let private_key = String::from_utf8(private_bytes)?;
+    Ok(String::from_utf8(private_bytes)?)
+}
 
-    Ok((public_key, private_key))
+/// Unlocks the primary key of `cert` so it can issue binding and revocation
+/// signatures. An empty passphrase is only accepted for unprotected keys.
+pub fn unlock_primary(cert: &Cert, passphrase: &str) -> Result<KeyPair> {
+    let key = cert
+        .primary_key()
+        .key()
+        .clone()
+        .parts_into_secret()
+        .map_err(|_| anyhow::anyhow!("Primary secret key is not available"))?;
+
+    let key = if key.secret().is_encrypted() {
+        key.decrypt_secret(&Password::from(passphrase))
+            .map_err(|_| anyhow::anyhow!("Wrong passphrase for key {}", cert.fingerprint()))?
+    } else {
+        key
+    };
+
+    key.into_keypair()
+}
+
+/// Adds a freshly generated subkey to `cert`.
+///
+/// `capability` is one of the names accepted by `key_flags_for`. The new subkey
+/// is protected with the same passphrase as the primary key, or left
+/// unprotected if the primary key is. Returns the updated cert together with
+/// the fingerprint of the new subkey.
+pub fn add_subkey(
+    cert: &Cert,
+    passphrase: &str,
+    capability: &str,
+    key_type: &str,
+    valid_seconds: Option<u64>,
+) -> Result<(Cert, openpgp::Fingerprint)> {
+    let p = StandardPolicy::new();
+
+    let flags = key_flags_for(capability)?;
+
+    let primary_signer = unlock_primary(cert, passphrase)?;
+
+    // Any passphrase given for an unprotected primary key is ignored, so the
+    // cert doesn't end up with mixed protection.
+    let mut key_builder = KeyBuilder::new(flags).set_cipher_suite(cipher_suite_for(key_type));
+    if !cert.primary_key().has_unencrypted_secret() && !passphrase.is_empty() {
+        key_builder = key_builder.set_password(Some(Password::from(passphrase)));
+    }
+
+    let vc = cert.with_policy(&p, None)?;
+    let mut subkey_builder = key_builder
+        .subkey(vc)?
+        .set_primary_key_signer(primary_signer);
+    if let Some(seconds) = valid_seconds {
+        subkey_builder =
+            subkey_builder.set_key_validity_period(std::time::Duration::from_secs(seconds))?;
+    }
+
+    let subkey_fingerprint = subkey_builder.key().fingerprint();
+    let updated = subkey_builder.attach_cert()?;
+
+    Ok((updated, subkey_fingerprint))
+}
+
+/// Revokes a single subkey of `cert`, leaving the primary key and the other
+/// subkeys untouched.
+///
+/// `reason` is one of `superseded`, `compromised` or `retired`; anything else
+/// is recorded as unspecified.
+pub fn revoke_subkey(
+    cert: &Cert,
+    passphrase: &str,
+    subkey_fingerprint: &str,
+    reason: &str,
+    message: &str,
+) -> Result<Cert> {
+    let fingerprint: openpgp::Fingerprint = subkey_fingerprint
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid subkey fingerprint: {}", subkey_fingerprint))?;
+    let subkey = cert
+        .keys()
+        .subkeys()
+        .find(|k| k.key().fingerprint() == fingerprint)
+        .ok_or_else(|| anyhow::anyhow!("Subkey not found: {}", subkey_fingerprint))?;
+
+    let code = match reason {
+        "superseded" => ReasonForRevocation::KeySuperseded,
+        "compromised" => ReasonForRevocation::KeyCompromised,
+        "retired" => ReasonForRevocation::KeyRetired,
+        _ => ReasonForRevocation::Unspecified,
+    };
+
+    let mut primary_signer = unlock_primary(cert, passphrase)?;
+    let revocation = SubkeyRevocationBuilder::new()
+        .set_reason_for_revocation(code, message.as_bytes())?
+        .build(&mut primary_signer, cert, subkey.key(), None)?;
+
+    cert.clone().insert_packets(revocation)
+}
+
+/// Sets a new expiry on the primary key.
+///
+/// Subkeys that would expire earlier than the new date are moved along with
+/// it; subkeys that never expire are left alone. Signing-capable subkeys need
+/// their own secret key for the back signature, so they are unlocked with the
+/// same passphrase.
+pub fn set_expiry(cert: &Cert, passphrase: &str, expiration: Option<std::time::SystemTime>) -> Result<Cert> {
+    let p = StandardPolicy::new();
+    let mut primary_signer = unlock_primary(cert, passphrase)?;
+
+    let mut signatures = cert.set_expiration_time(&p, None, &mut primary_signer, expiration)?;
+
+    let vc = cert.with_policy(&p, None)?;
+    for ka in vc.keys().subkeys().revoked(false) {
+        let follows = match (ka.key_expiration_time(), expiration) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(current), Some(new)) => current < new,
+        };
+        if !follows {
+            continue;
+        }
+
+        let mut subkey_signer = if ka.for_signing() || ka.for_certification() {
+            Some(unlock_key(ka.key(), passphrase)?)
+        } else {
+            None
+        };
+        signatures.extend(ka.set_expiration_time(
+            &mut primary_signer,
+            subkey_signer.as_mut().map(|s| s as &mut dyn openpgp::crypto::Signer),
+            expiration,
+        )?);
+    }
+
+    cert.clone().insert_packets(signatures)
+}
+
+fn unlock_key<R: key::KeyRole + Clone>(key: &Key<key::PublicParts, R>, passphrase: &str) -> Result<KeyPair> {
+    let fingerprint = key.fingerprint();
+    let key = key
+        .clone()
+        .parts_into_secret()
+        .map_err(|_| anyhow::anyhow!("Secret key {} is not available", fingerprint))?;
+
+    let key = if key.secret().is_encrypted() {
+        key.decrypt_secret(&Password::from(passphrase))
+            .map_err(|_| anyhow::anyhow!("Wrong passphrase for key {}", fingerprint))?
+    } else {
+        key
+    };
+
+    key.into_keypair()
+}
+
+/// Re-protects every secret key packet of `cert`.
+///
+/// Encrypted key material is first unlocked with `old_passphrase`. If
+/// `new_passphrase` is empty the keys are left unprotected, otherwise they
+/// are encrypted with it. Keys that were stored unprotected can be protected
+/// by passing any `old_passphrase`.
+pub fn change_passphrase(cert: Cert, old_passphrase: &str, new_passphrase: &str) -> Result<Cert> {
+    let old_password = Password::from(old_passphrase);
+    let new_password = if new_passphrase.is_empty() {
+        None
+    } else {
+        Some(Password::from(new_passphrase))
+    };
+
+    let packets: Vec<openpgp::packet::Packet> = cert.into();
+
+    let packets = packets
+        .into_iter()
+        .map(|packet| match packet {
+            openpgp::packet::Packet::SecretKey(key) => {
+                reprotect_key(key, &old_password, new_password.as_ref())
+                    .map(openpgp::packet::Packet::SecretKey)
+            }
+            openpgp::packet::Packet::SecretSubkey(key) => {
+                reprotect_key(key, &old_password, new_password.as_ref())
+                    .map(openpgp::packet::Packet::SecretSubkey)
+            }
+            p => Ok(p),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Cert::try_from(packets)
+}
+
+fn reprotect_key<R: key::KeyRole>(
+    key: Key<key::SecretParts, R>,
+    old_password: &Password,
+    new_password: Option<&Password>,
+) -> Result<Key<key::SecretParts, R>> {
+    let fingerprint = key.fingerprint();
+
+    let key = if key.secret().is_encrypted() {
+        key.decrypt_secret(old_password)
+            .map_err(|_| anyhow::anyhow!("Wrong passphrase for key {}", fingerprint))?
+    } else {
+        key
+    };
+
+    match new_password {
+        Some(password) => key
+            .encrypt_secret(password)
+            .map_err(|e| anyhow::anyhow!("Failed to protect key {}: {}", fingerprint, e)),
+        None => Ok(key),
+    }
+}
+
+/// Reports, for every secret key packet in `cert`, whether its key material
+/// is encrypted and how.
+pub fn protection_status(cert: &Cert) -> Vec<SecretKeyProtection> {
+    cert.keys()
+        .secret()
+        .map(|ka| {
+            let key = ka.key();
+            let (s2k, cipher) = match key.secret() {
+                SecretKeyMaterial::Encrypted(e) => {
+                    (Some(describe_s2k(e.s2k())), Some(e.algo().to_string()))
+                }
+                SecretKeyMaterial::Unencrypted(_) => (None, None),
+            };
+
+            SecretKeyProtection {
+                fingerprint: key.fingerprint().to_hex(),
+                is_primary: ka.primary(),
+                encrypted: key.secret().is_encrypted(),
+                s2k,
+                cipher,
+            }
+        })
+        .collect()
+}
+
+// Short S2K description without the salt, which is noise for the user.
+fn describe_s2k(s2k: &S2K) -> String {
+    #[allow(deprecated)]
+    match s2k {
+        S2K::Iterated { hash, hash_bytes, .. } => {
+            format!("Iterated+Salted ({}, {} bytes)", hash, hash_bytes)
+        }
+        S2K::Salted { hash, .. } => format!("Salted ({})", hash),
+        S2K::Simple { hash } => format!("Simple ({})", hash),
+        S2K::Implicit => "Implicit".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Strips a cert down to what a recipient needs: the current self-signature
+/// of every valid component plus self-revocations. Third-party
+/// certifications and superseded self-signatures are dropped.
+pub fn minimize_cert(cert: &Cert) -> Result<Cert> {
+    let p = StandardPolicy::new();
+    let vc = cert.with_policy(&p, None)?;
+
+    // take_secret() drops any secret key material carried along by the clone.
+    let mut packets: Vec<openpgp::packet::Packet> =
+        vec![cert.primary_key().key().clone().take_secret().0.into()];
+    if let Ok(sig) = vc.direct_key_signature() {
+        packets.push(sig.clone().into());
+    }
+    packets.extend(cert.primary_key().self_revocations().cloned().map(Into::into));
+
+    for ua in vc.userids() {
+        packets.push(ua.userid().clone().into());
+        packets.push(ua.binding_signature().clone().into());
+        packets.extend(ua.self_revocations().cloned().map(Into::into));
+    }
+
+    for ka in vc.keys().subkeys() {
+        packets.push(ka.key().clone().take_secret().0.into());
+        packets.push(ka.binding_signature().clone().into());
+        packets.extend(ka.self_revocations().cloned().map(Into::into));
+    }
+
+    Cert::try_from(packets)
+}
+
+/// Serializes the public parts of several certs as one keyring, either binary
+/// or as a single ASCII armored public key block. Local (non-exportable)
+/// certifications are left out.
+pub fn serialize_public_keyring(certs: &[Cert], armor: bool) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    if armor {
+        let message = Message::new(&mut bytes);
+        let mut armor_writer = Armorer::new(message)
+            .kind(openpgp::armor::Kind::PublicKey)
+            .build()?;
+        for cert in certs {
+            cert.export(&mut armor_writer)?;
+        }
+        armor_writer.finalize()?;
+    } else {
+        for cert in certs {
+            cert.export(&mut bytes)?;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Serializes the certs that carry secret key material as one secret keyring,
+/// like `gpg --export-secret-keys`. Secret keys are written as stored, so
+/// protected keys stay protected; local certifications are left out.
+pub fn serialize_secret_keyring(certs: &[Cert], armor: bool) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    if armor {
+        let message = Message::new(&mut bytes);
+        let mut armor_writer = Armorer::new(message)
+            .kind(openpgp::armor::Kind::SecretKey)
+            .build()?;
+        for cert in certs.iter().filter(|c| c.is_tsk()) {
+            cert.as_tsk().export(&mut armor_writer)?;
+        }
+        armor_writer.finalize()?;
+    } else {
+        for cert in certs.iter().filter(|c| c.is_tsk()) {
+            cert.as_tsk().export(&mut bytes)?;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Options for certifying the user IDs of someone else's cert.
+pub struct CertifyOptions<'a> {
+    /// User IDs to certify; all user IDs of the target if empty.
+    pub user_ids: &'a [String],
+    /// 0 for a plain certification, 1 or more to make the target a trusted
+    /// introducer for that many levels.
+    pub trust_depth: u8,
+    /// Trust amount for trust signatures: 120 is full, 60 is partial.
+    pub trust_amount: u8,
+    pub valid_seconds: Option<u64>,
+    /// Local certifications are kept in the vault but never exported.
+    pub exportable: bool,
+}
+
+/// Certifies user IDs on `target` with the primary key of `signer` and
+/// returns `target` with the new certifications merged in.
+pub fn certify_userids(
+    signer: &Cert,
+    passphrase: &str,
+    target: &Cert,
+    options: &CertifyOptions,
+) -> Result<Cert> {
+    if signer.fingerprint() == target.fingerprint() {
+        return Err(anyhow::anyhow!("A key cannot certify itself"));
+    }
+
+    let p = StandardPolicy::new();
+    let vc = target.with_policy(&p, None)?;
+    let mut keypair = unlock_primary(signer, passphrase)?;
+
+    let mut certifications = Vec::new();
+    for ua in vc.userids() {
+        let value = String::from_utf8_lossy(ua.userid().value()).to_string();
+        if !options.user_ids.is_empty() && !options.user_ids.contains(&value) {
+            continue;
+        }
+
+        let mut builder = SignatureBuilder::new(SignatureType::GenericCertification)
+            .set_exportable_certification(options.exportable)?;
+        if options.trust_depth > 0 {
+            builder = builder.set_trust_signature(options.trust_depth, options.trust_amount)?;
+        }
+        if let Some(seconds) = options.valid_seconds {
+            builder = builder.set_signature_validity_period(std::time::Duration::from_secs(seconds))?;
+        }
+
+        let sig = builder.sign_userid_binding(&mut keypair, target.primary_key().key(), ua.userid())?;
+        certifications.push(sig);
+    }
+
+    if certifications.is_empty() {
+        return Err(anyhow::anyhow!("No matching valid user IDs to certify"));
+    }
+
+    target.clone().insert_packets(certifications)
 }
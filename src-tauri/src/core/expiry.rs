@@ -0,0 +1,79 @@
+// Detection of keys that are about to expire
+use crate::core::storage::Vault;
+use crate::models::{ExpiringKey, KeyMetadata};
+use anyhow::Result;
+use rusqlite::params;
+use sequoia_openpgp as openpgp;
+use openpgp::parse::Parse;
+use openpgp::policy::StandardPolicy;
+use openpgp::types::RevocationStatus;
+
+/// Returns the private keys (and, if asked, favorite public keys) that expire
+/// within `window_days`, soonest first.
+///
+/// Private keys that expired within the last `window_days` are included
+/// since their owner can still extend them; expired public keys are not.
+/// Revoked keys are never reported.
+pub fn expiring_keys(vault: &Vault, window_days: u32, include_favorite_public: bool) -> Result<Vec<ExpiringKey>> {
+    let p = StandardPolicy::new();
+    let now = chrono::Utc::now();
+    let soon = now + chrono::Duration::days(window_days as i64);
+    let expired_since = now - chrono::Duration::days(window_days as i64);
+
+    // expires_at is stored as RFC 3339 in UTC, which sorts lexically.
+    let conn = vault.conn.lock().unwrap();
+    let mut stmt = conn.prepare(
+        "SELECT metadata_json, key_content FROM keys
+         WHERE metadata_json IS NOT NULL AND expires_at IS NOT NULL AND expires_at <= ?1
+           AND ((is_private = 1 AND expires_at > ?4) OR (?2 AND is_favorite = 1 AND expires_at > ?3))
+         ORDER BY expires_at",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            soon.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            include_favorite_public,
+            now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            expired_since.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        ],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+    )?;
+
+    let mut keys = Vec::new();
+    // Unreadable rows are skipped, as in the key list.
+    for (json, content) in rows.flatten() {
+        let metadata: KeyMetadata = match serde_json::from_str(&json) {
+            Ok(m) => m,
+            Err(e) => {
+                log::warn!("Skipping key with unreadable metadata: {}", e);
+                continue;
+            }
+        };
+        // Revocation isn't part of the metadata, so it is read from the key.
+        let revoked = content
+            .and_then(|c| openpgp::Cert::from_bytes(c.as_bytes()).ok())
+            .is_some_and(|cert| matches!(cert.revocation_status(&p, None), RevocationStatus::Revoked(_)));
+        if revoked {
+            continue;
+        }
+        let expires_at = match metadata.expires_at {
+            Some(e) => e,
+            None => continue,
+        };
+        let expires = match chrono::DateTime::parse_from_rfc3339(&expires_at) {
+            Ok(t) => t,
+            Err(e) => {
+                log::warn!("Skipping key {} with invalid expiry {}: {}", metadata.fingerprint, expires_at, e);
+                continue;
+            }
+        };
+
+        keys.push(ExpiringKey {
+            fingerprint: metadata.fingerprint,
+            user_id: metadata.user_id,
+            days_left: (expires.with_timezone(&chrono::Utc) - now).num_days(),
+            expires_at,
+            is_private: metadata.is_private,
+        });
+    }
+    Ok(keys)
+}
@@ -0,0 +1,265 @@
+// Fingerprint verification aids
+//
+// Ways to compare a fingerprint that are less error-prone than reading out
+// 40 hex digits: the PGP word list, OpenSSH-style randomart and an
+// `openpgp4fpr:` URI for QR codes.
+use anyhow::{anyhow, Result};
+use qrcode::render::svg;
+use qrcode::{EcLevel, QrCode};
+use sequoia_openpgp as openpgp;
+use openpgp::Fingerprint;
+
+pub const URI_SCHEME: &str = "openpgp4fpr:";
+
+/// Reads a fingerprint as the PGP word list: bytes at even positions map to
+/// two-syllable words, at odd positions to three-syllable words, so swapped
+/// or dropped words are noticed.
+pub fn words(fingerprint: &Fingerprint) -> Vec<&'static str> {
+    fingerprint
+        .as_bytes()
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| if i % 2 == 0 { EVEN_WORDS[b as usize] } else { ODD_WORDS[b as usize] })
+        .collect()
+}
+
+// Randomart board, the same as OpenSSH's.
+const FIELD_WIDTH: usize = 17;
+const FIELD_HEIGHT: usize = 9;
+const SYMBOLS: &[u8] = b" .o+=*BOX@%&#/^SE";
+
+/// Draws the fingerprint as OpenSSH draws host key fingerprints ("drunken
+/// bishop"), framed with `title` on top and the fingerprint hash below.
+pub fn randomart(fingerprint: &Fingerprint, title: &str) -> String {
+    let start = (FIELD_WIDTH / 2, FIELD_HEIGHT / 2);
+    let mut field = [[0u8; FIELD_HEIGHT]; FIELD_WIDTH];
+    let (mut x, mut y) = start;
+    // The last two symbols mark start and end.
+    let max = (SYMBOLS.len() - 1) as u8;
+
+    for &byte in fingerprint.as_bytes() {
+        let mut input = byte;
+        for _ in 0..4 {
+            x = if input & 0x1 != 0 { (x + 1).min(FIELD_WIDTH - 1) } else { x.saturating_sub(1) };
+            y = if input & 0x2 != 0 { (y + 1).min(FIELD_HEIGHT - 1) } else { y.saturating_sub(1) };
+            if field[x][y] < max - 2 {
+                field[x][y] += 1;
+            }
+            input >>= 2;
+        }
+    }
+    field[start.0][start.1] = max - 1;
+    field[x][y] = max;
+
+    let hash = match fingerprint {
+        Fingerprint::V4(_) => "SHA1",
+        _ => "SHA256",
+    };
+
+    let mut out = border(title);
+    for row in 0..FIELD_HEIGHT {
+        out.push('|');
+        for column in field.iter() {
+            out.push(SYMBOLS[column[row] as usize] as char);
+        }
+        out.push_str("|\n");
+    }
+    out.push_str(&border(hash));
+    out
+}
+
+// "+---[title]----+", centered like OpenSSH's, the title cut to fit.
+fn border(label: &str) -> String {
+    let label: String = format!("[{}]", label).chars().take(FIELD_WIDTH).collect();
+    let len = label.chars().count();
+    let left = (FIELD_WIDTH - len) / 2;
+    format!("+{}{}{}+\n", "-".repeat(left), label, "-".repeat(FIELD_WIDTH - len - left))
+}
+
+/// The `openpgp4fpr:` URI of a fingerprint, as shown in QR codes by
+/// OpenKeychain and Delta Chat.
+pub fn uri(fingerprint: &Fingerprint) -> String {
+    format!("{}{}", URI_SCHEME, fingerprint.to_hex())
+}
+
+/// Parses a scanned `openpgp4fpr:` URI. The scheme is case-insensitive and
+/// anything after the fingerprint (`#` parameters added by Delta Chat) is
+/// ignored.
+pub fn parse_uri(uri: &str) -> Result<Fingerprint> {
+    let uri = uri.trim();
+    let rest = uri
+        .get(..URI_SCHEME.len())
+        .filter(|scheme| scheme.eq_ignore_ascii_case(URI_SCHEME))
+        .map(|_| &uri[URI_SCHEME.len()..])
+        .ok_or_else(|| anyhow!("Not an {} URI", URI_SCHEME))?;
+
+    let hex = rest.split(['#', '?']).next().unwrap_or_default();
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) || !matches!(hex.len(), 40 | 64) {
+        return Err(anyhow!("The URI does not contain a valid fingerprint"));
+    }
+    hex.parse()
+}
+
+/// Encodes `data` as a QR code in SVG.
+pub fn qr_svg(data: &str) -> Result<String> {
+    let code = QrCode::with_error_correction_level(data.as_bytes(), EcLevel::M)?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .dark_color(svg::Color("#000000"))
+        .light_color(svg::Color("#ffffff"))
+        .build())
+}
+
+// Two-syllable words, for bytes at even positions.
+const EVEN_WORDS: [&str; 256] = [
+    "aardvark", "absurd", "accrue", "acme", "adrift", "adult", "afflict", "ahead", "aimless",
+    "Algol", "allow", "alone", "ammo", "ancient", "apple", "artist", "assume", "Athens", "atlas",
+    "Aztec", "baboon", "backfield", "backward", "banjo", "beaming", "bedlamp", "beehive", "beeswax",
+    "befriend", "Belfast", "berserk", "billiard", "bison", "blackjack", "blockade", "blowtorch",
+    "bluebird", "bombast", "bookshelf", "brackish", "breadline", "breakup", "brickyard",
+    "briefcase", "Burbank", "button", "buzzard", "cement", "chairlift", "chatter", "checkup",
+    "chisel", "choking", "chopper", "Christmas", "clamshell", "classic", "classroom", "cleanup",
+    "clockwork", "cobra", "commence", "concert", "cowbell", "crackdown", "cranky", "crowfoot",
+    "crucial", "crumpled", "crusade", "cubic", "dashboard", "deadbolt", "deckhand", "dogsled",
+    "dragnet", "drainage", "dreadful", "drifter", "dropper", "drumbeat", "drunken", "Dupont",
+    "dwelling", "eating", "edict", "egghead", "eightball", "endorse", "endow", "enlist", "erase",
+    "escape", "exceed", "eyeglass", "eyetooth", "facial", "fallout", "flagpole", "flatfoot",
+    "flytrap", "fracture", "framework", "freedom", "frighten", "gazelle", "Geiger", "glitter",
+    "glucose", "goggles", "goldfish", "gremlin", "guidance", "hamlet", "highchair", "hockey",
+    "indoors", "indulge", "inverse", "involve", "island", "jawbone", "keyboard", "kickoff", "kiwi",
+    "klaxon", "locale", "lockup", "merit", "minnow", "miser", "Mohawk", "mural", "music",
+    "necklace", "Neptune", "newborn", "nightbird", "Oakland", "obtuse", "offload", "optic", "orca",
+    "payday", "peachy", "pheasant", "physique", "playhouse", "Pluto", "preclude", "prefer",
+    "preshrunk", "printer", "prowler", "pupil", "puppy", "python", "quadrant", "quiver", "quota",
+    "ragtime", "ratchet", "rebirth", "reform", "regain", "reindeer", "rematch", "repay", "retouch",
+    "revenge", "reward", "rhythm", "ribcage", "ringbolt", "robust", "rocker", "ruffled", "sailboat",
+    "sawdust", "scallion", "scenic", "scorecard", "Scotland", "seabird", "select", "sentence",
+    "shadow", "shamrock", "showgirl", "skullcap", "skydive", "slingshot", "slowdown", "snapline",
+    "snapshot", "snowcap", "snowslide", "solo", "southward", "soybean", "spaniel", "spearhead",
+    "spellbind", "spheroid", "spigot", "spindle", "spyglass", "stagehand", "stagnate", "stairway",
+    "standard", "stapler", "steamship", "sterling", "stockman", "stopwatch", "stormy", "sugar",
+    "surmount", "suspense", "sweatband", "swelter", "tactics", "talon", "tapeworm", "tempest",
+    "tiger", "tissue", "tonic", "topmost", "tracker", "transit", "trauma", "treadmill", "Trojan",
+    "trouble", "tumor", "tunnel", "tycoon", "uncut", "unearth", "unwind", "uproot", "upset",
+    "upshot", "vapor", "village", "virus", "Vulcan", "waffle", "wallet", "watchword", "wayside",
+    "willow", "woodlark", "Zulu",
+];
+
+// Three-syllable words, for bytes at odd positions.
+const ODD_WORDS: [&str; 256] = [
+    "adroitness", "adviser", "aftermath", "aggregate", "alkali", "almighty", "amulet", "amusement",
+    "antenna", "applicant", "Apollo", "armistice", "article", "asteroid", "Atlantic", "atmosphere",
+    "autopsy", "Babylon", "backwater", "barbecue", "belowground", "bifocals", "bodyguard",
+    "bookseller", "borderline", "bottomless", "Bradbury", "bravado", "Brazilian", "breakaway",
+    "Burlington", "businessman", "butterfat", "Camelot", "candidate", "cannonball", "Capricorn",
+    "caravan", "caretaker", "celebrate", "cellulose", "certify", "chambermaid", "Cherokee",
+    "Chicago", "clergyman", "coherence", "combustion", "commando", "company", "component",
+    "concurrent", "confidence", "conformist", "congregate", "consensus", "consulting", "corporate",
+    "corrosion", "councilman", "crossover", "crucifix", "cumbersome", "customer", "Dakota",
+    "decadence", "December", "decimal", "designing", "detector", "detergent", "determine",
+    "dictator", "dinosaur", "direction", "disable", "disbelief", "disruptive", "distortion",
+    "document", "embezzle", "enchanting", "enrollment", "enterprise", "equation", "equipment",
+    "escapade", "Eskimo", "everyday", "examine", "existence", "exodus", "fascinate", "filament",
+    "finicky", "forever", "fortitude", "frequency", "gadgetry", "Galveston", "getaway", "glossary",
+    "gossamer", "graduate", "gravity", "guitarist", "hamburger", "Hamilton", "handiwork",
+    "hazardous", "headwaters", "hemisphere", "hesitate", "hideaway", "holiness", "hurricane",
+    "hydraulic", "impartial", "impetus", "inception", "indigo", "inertia", "infancy", "inferno",
+    "informant", "insincere", "insurgent", "integrate", "intention", "inventive", "Istanbul",
+    "Jamaica", "Jupiter", "leprosy", "letterhead", "liberty", "maritime", "matchmaker", "maverick",
+    "Medusa", "megaton", "microscope", "microwave", "midsummer", "millionaire", "miracle",
+    "misnomer", "molasses", "molecule", "Montana", "monument", "mosquito", "narrative", "nebula",
+    "newsletter", "Norwegian", "October", "Ohio", "onlooker", "opulent", "Orlando", "outfielder",
+    "Pacific", "pandemic", "Pandora", "paperweight", "paragon", "paragraph", "paramount",
+    "passenger", "pedigree", "Pegasus", "penetrate", "perceptive", "performance", "pharmacy",
+    "phonetic", "photograph", "pioneer", "pocketful", "politeness", "positive", "potato",
+    "processor", "provincial", "proximate", "puberty", "publisher", "pyramid", "quantity",
+    "racketeer", "rebellion", "recipe", "recover", "repellent", "replica", "reproduce", "resistor",
+    "responsive", "retraction", "retrieval", "retrospect", "revenue", "revival", "revolver",
+    "sandalwood", "sardonic", "Saturday", "savagery", "scavenger", "sensation", "sociable",
+    "souvenir", "specialist", "speculate", "stethoscope", "stupendous", "supportive", "surrender",
+    "suspicious", "sympathy", "tambourine", "telephone", "therapist", "tobacco", "tolerance",
+    "tomorrow", "torpedo", "tradition", "travesty", "trombonist", "truncated", "typewriter",
+    "ultimate", "undaunted", "underfoot", "unicorn", "unify", "universe", "unravel", "upcoming",
+    "vacancy", "vagabond", "vertigo", "Virginia", "visitor", "vocalist", "voyager", "warranty",
+    "Waterloo", "whimsical", "Wichita", "Wilmington", "Wyoming", "yesteryear", "Yucatan",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The example from the PGP word list's description.
+    const FINGERPRINT: &str = "E58294F2E9A227486E8B061B31CC528FD7FA3F19";
+
+    #[test]
+    fn word_list() {
+        let fingerprint: Fingerprint = FINGERPRINT.parse().unwrap();
+        assert_eq!(
+            words(&fingerprint).join(" "),
+            "topmost Istanbul Pluto vagabond treadmill Pacific brackish dictator goldfish Medusa \
+             afflict bravado chatter revolver Dupont midsummer stopwatch whimsical cowbell bottomless"
+        );
+
+        assert_eq!((EVEN_WORDS[0], ODD_WORDS[0]), ("aardvark", "adroitness"));
+        assert_eq!((EVEN_WORDS[255], ODD_WORDS[255]), ("Zulu", "Yucatan"));
+        // Both lists are complete and no word appears twice.
+        let mut all: Vec<&str> = EVEN_WORDS.iter().chain(ODD_WORDS.iter()).copied().collect();
+        all.sort_unstable();
+        all.dedup();
+        assert_eq!(all.len(), 512);
+    }
+
+    #[test]
+    fn randomart_matches_openssh() {
+        // The example from "The drunken bishop" paper describing OpenSSH's
+        // randomart; its framing differs from ours, the field must not.
+        let fingerprint = Fingerprint::from_bytes(&[
+            0xfc, 0x94, 0xb0, 0xc1, 0xe5, 0xb0, 0x98, 0x7c, 0x58, 0x43, 0x99, 0x76, 0x97, 0xee, 0x9f, 0xb7,
+        ]);
+        let art = randomart(&fingerprint, "RSA 2048");
+        let lines: Vec<&str> = art.lines().collect();
+        assert_eq!(lines[0], "+---[RSA 2048]----+");
+        assert_eq!(
+            lines[1..10],
+            [
+                "|       .=o.  .   |",
+                "|     . *+*. o    |",
+                "|      =.*..o     |",
+                "|       o + ..    |",
+                "|        S o.     |",
+                "|         o  .    |",
+                "|          .  . . |",
+                "|              o .|",
+                "|               E.|",
+            ]
+        );
+
+        let fingerprint: Fingerprint = FINGERPRINT.parse().unwrap();
+        let art = randomart(&fingerprint, "a title far too long for the frame");
+        assert_eq!(art.lines().count(), 11);
+        assert!(art.lines().all(|l| l.chars().count() == FIELD_WIDTH + 2));
+        assert!(art.ends_with("+-----[SHA1]------+\n"));
+    }
+
+    #[test]
+    fn uri_round_trip() {
+        let fingerprint: Fingerprint = FINGERPRINT.parse().unwrap();
+        assert_eq!(parse_uri(&uri(&fingerprint)).unwrap(), fingerprint);
+
+        // As scanned from a Delta Chat QR code.
+        let scanned = format!("OPENPGP4FPR:{}#a=alice%40example.org&n=Alice&i=abc&s=def", FINGERPRINT);
+        assert_eq!(parse_uri(&scanned).unwrap(), fingerprint);
+        assert_eq!(parse_uri(&format!(" openpgp4fpr:{} ", FINGERPRINT.to_lowercase())).unwrap(), fingerprint);
+    }
+
+    #[test]
+    fn uri_rejected() {
+        assert!(parse_uri(&format!("openpgp4fpr:{}", &FINGERPRINT[..16])).is_err());
+        assert!(parse_uri(&format!("openpgp4fpr:{}0", FINGERPRINT)).is_err());
+        assert!(parse_uri(&format!("openpgp4fpr:{}Z", &FINGERPRINT[..39])).is_err());
+        assert!(parse_uri(&format!("mailto:{}", FINGERPRINT)).is_err());
+        assert!(parse_uri(FINGERPRINT).is_err());
+        assert!(parse_uri("").is_err());
+    }
+}
@@ -0,0 +1,157 @@
+// GnuPG interoperability: migrating from a GnuPG home, ownertrust files
+//
+// GnuPG 2.1+ keeps public keys in `pubring.kbx` (older homes in
+// `pubring.gpg`) and secret keys in `private-keys-v1.d`, in gpg-agent's own
+// format, which is not OpenPGP. Secret keys are therefore taken from
+// `gpg --export-secret-keys` files, or from `secring.gpg` of GnuPG 1.x homes.
+use crate::models::OwnerTrust;
+use anyhow::{anyhow, Result};
+use sequoia_openpgp as openpgp;
+use openpgp::cert::CertParser;
+use openpgp::parse::Parse;
+use openpgp::Cert;
+use std::path::{Path, PathBuf};
+
+// Public keyrings in order of preference; GnuPG 2.1+ stops updating
+// pubring.gpg once pubring.kbx exists.
+const PUBLIC_KEYRINGS: &[&str] = &["pubring.kbx", "pubring.gpg"];
+const SECRET_KEYRING: &str = "secring.gpg";
+const PRIVATE_KEYS_DIR: &str = "private-keys-v1.d";
+
+// Trust values of `gpg --export-ownertrust`; the upper bits are flags.
+const TRUST_MASK: u8 = 0x0F;
+const TRUST_NEVER: u8 = 3;
+const TRUST_MARGINAL: u8 = 4;
+const TRUST_FULLY: u8 = 5;
+const TRUST_ULTIMATE: u8 = 6;
+
+/// Keys read from a GnuPG home and export files.
+#[derive(Default)]
+pub struct GnupgKeys {
+    pub certs: Vec<Cert>,
+    pub files: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// The GnuPG home of the current user: `$GNUPGHOME`, `%APPDATA%\gnupg` on
+/// Windows and `~/.gnupg` elsewhere.
+pub fn default_home() -> Option<PathBuf> {
+    if let Some(home) = std::env::var_os("GNUPGHOME") {
+        return Some(PathBuf::from(home));
+    }
+    let dirs = directories::BaseDirs::new()?;
+    if cfg!(windows) {
+        Some(dirs.data_dir().join("gnupg"))
+    } else {
+        Some(dirs.home_dir().join(".gnupg"))
+    }
+}
+
+/// Reads the public (and GnuPG 1.x secret) keyring of the home at `dir`.
+pub fn read_home(dir: &Path, keys: &mut GnupgKeys) -> Result<()> {
+    if !dir.is_dir() {
+        return Err(anyhow!("GnuPG home {} does not exist", dir.display()));
+    }
+
+    match PUBLIC_KEYRINGS.iter().map(|name| dir.join(name)).find(|p| p.is_file()) {
+        Some(path) => read_file(&path, keys)?,
+        None => keys
+            .warnings
+            .push(format!("No public keyring found in {}", dir.display())),
+    }
+
+    let secring = dir.join(SECRET_KEYRING);
+    if secring.is_file() {
+        read_file(&secring, keys)?;
+    }
+
+    // gpg-agent's key files cannot be read without the agent; point the user
+    // to the export instead.
+    let agent_keys = std::fs::read_dir(dir.join(PRIVATE_KEYS_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().extension().is_some_and(|ext| ext == "key"))
+                .count()
+        })
+        .unwrap_or(0);
+    if agent_keys > 0 {
+        keys.warnings.push(format!(
+            "{} secret key files in {} are stored in gpg-agent's format and were not read; \
+             import the output of `gpg --export-secret-keys` to migrate secret keys",
+            agent_keys, PRIVATE_KEYS_DIR
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reads every cert of a keybox, binary or armored keyring file.
+pub fn read_file(path: &Path, keys: &mut GnupgKeys) -> Result<()> {
+    let data = std::fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+
+    for block in crate::core::keyfile::key_blocks(&data)? {
+        for cert in CertParser::from_bytes(&block)? {
+            match cert {
+                Ok(cert) => keys.certs.push(cert),
+                Err(e) => keys
+                    .warnings
+                    .push(format!("Skipped an unreadable key in {}: {}", path.display(), e)),
+            }
+        }
+    }
+
+    keys.files.push(path.display().to_string());
+    Ok(())
+}
+
+/// Parses the output of `gpg --export-ownertrust`: `FINGERPRINT:VALUE:`
+/// lines, with `#` comments.
+pub fn parse_ownertrust(text: &str) -> Result<Vec<(String, OwnerTrust)>> {
+    let mut entries = Vec::new();
+    for (no, line) in text.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split(':');
+        let fingerprint = fields.next().unwrap_or_default();
+        let value: u8 = fields
+            .next()
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| anyhow!("Malformed ownertrust line {}", no))?;
+        if !fingerprint.chars().all(|c| c.is_ascii_hexdigit()) || !matches!(fingerprint.len(), 40 | 64) {
+            return Err(anyhow!("Invalid fingerprint on ownertrust line {}", no));
+        }
+
+        let trust = match value & TRUST_MASK {
+            TRUST_NEVER => OwnerTrust::Never,
+            TRUST_MARGINAL => OwnerTrust::Marginal,
+            TRUST_FULLY => OwnerTrust::Full,
+            TRUST_ULTIMATE => OwnerTrust::Ultimate,
+            _ => OwnerTrust::Unknown,
+        };
+        entries.push((fingerprint.to_uppercase(), trust));
+    }
+    Ok(entries)
+}
+
+/// Writes owner trust in the format of `gpg --export-ownertrust`, ready for
+/// `gpg --import-ownertrust`. Keys without an owner trust are left out.
+pub fn format_ownertrust(entries: &[(String, OwnerTrust)]) -> String {
+    let mut out = format!(
+        "# List of assigned trustvalues, created {}\n# (Use \"gpg --import-ownertrust\" to restore them)\n",
+        chrono::Utc::now().format("%a %b %e %H:%M:%S %Y UTC")
+    );
+    for (fingerprint, trust) in entries {
+        let value = match trust {
+            OwnerTrust::Never => TRUST_NEVER,
+            OwnerTrust::Marginal => TRUST_MARGINAL,
+            OwnerTrust::Full => TRUST_FULLY,
+            OwnerTrust::Ultimate => TRUST_ULTIMATE,
+            OwnerTrust::Unknown => continue,
+        };
+        out.push_str(&format!("{}:{}:\n", fingerprint.to_uppercase(), value));
+    }
+    out
+}
@@ -0,0 +1,232 @@
+// Detailed inspection of stored certs
+
+use crate::models::{
+    CertificationDetails, KeyAlgorithm, KeyComponentDetails, KeyDetails, KeyType, UserIdDetails,
+};
+use anyhow::Result;
+use sequoia_openpgp as openpgp;
+use openpgp::cert::prelude::*;
+use openpgp::crypto::mpi;
+use openpgp::packet::{key, Key};
+use openpgp::policy::StandardPolicy;
+use openpgp::types::{KeyFlags, RevocationStatus};
+use openpgp::Cert;
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+fn to_rfc3339(t: SystemTime) -> String {
+    let datetime: chrono::DateTime<chrono::Utc> = t.into();
+    datetime.to_rfc3339()
+}
+
+/// Returns the algorithm name, key size in bits and, for ECC keys, the curve.
+pub fn key_algorithm<P: key::KeyParts, R: key::KeyRole>(
+    key: &Key<P, R>,
+) -> (String, Option<usize>, Option<String>) {
+    let curve = match key.mpis() {
+        mpi::PublicKey::EdDSA { curve, .. }
+        | mpi::PublicKey::ECDSA { curve, .. }
+        | mpi::PublicKey::ECDH { curve, .. } => Some(curve.to_string()),
+        _ => None,
+    };
+    (key.pk_algo().to_string(), key.mpis().bits(), curve)
+}
+
+/// Derives the key type shown in the key list from the cert's primary key.
+pub fn key_type(cert: &Cert) -> KeyType {
+    use openpgp::types::PublicKeyAlgorithm;
+
+    let primary = cert.primary_key().key();
+    let (_, bits, curve) = key_algorithm(primary);
+
+    #[allow(deprecated)]
+    let algorithm = match primary.pk_algo() {
+        PublicKeyAlgorithm::RSAEncryptSign => KeyAlgorithm::Rsa,
+        PublicKeyAlgorithm::RSASign => KeyAlgorithm::RsaSignOnly,
+        PublicKeyAlgorithm::RSAEncrypt => KeyAlgorithm::RsaEncryptOnly,
+        PublicKeyAlgorithm::DSA => KeyAlgorithm::Dsa,
+        PublicKeyAlgorithm::ElGamalEncrypt | PublicKeyAlgorithm::ElGamalEncryptSign => {
+            KeyAlgorithm::ElGamal
+        }
+        PublicKeyAlgorithm::EdDSA => KeyAlgorithm::EdDsa,
+        PublicKeyAlgorithm::ECDSA => KeyAlgorithm::Ecdsa,
+        PublicKeyAlgorithm::ECDH => KeyAlgorithm::Ecdh,
+        _ => KeyAlgorithm::Unknown,
+    };
+
+    KeyType { algorithm, bits, curve }
+}
+
+fn capabilities(flags: &KeyFlags) -> Vec<String> {
+    let mut caps = Vec::new();
+    if flags.for_certification() {
+        caps.push("certify".to_string());
+    }
+    if flags.for_signing() {
+        caps.push("sign".to_string());
+    }
+    if flags.for_transport_encryption() {
+        caps.push("encrypt_transport".to_string());
+    }
+    if flags.for_storage_encryption() {
+        caps.push("encrypt_storage".to_string());
+    }
+    if flags.for_authentication() {
+        caps.push("authenticate".to_string());
+    }
+    caps
+}
+
+// Feature flags by bit (RFC 9580, section 5.2.3.32, and LibrePGP).
+fn feature_name(bit: usize) -> String {
+    match bit {
+        0 => "seipdv1".to_string(),
+        1 => "ocb_aead".to_string(), // LibrePGP
+        2 => "v5_keys".to_string(),  // LibrePGP
+        3 => "seipdv2".to_string(),
+        n => format!("unknown_{}", n),
+    }
+}
+
+/// Parses every component of `cert`. `vault_certs` is used to resolve and
+/// verify the issuers of third-party certifications.
+pub fn key_details(cert: &Cert, vault_certs: &[Cert]) -> Result<KeyDetails> {
+    let p = StandardPolicy::new();
+    let (vc, policy_error) = match cert.with_policy(&p, None) {
+        Ok(vc) => (Some(vc), None),
+        Err(e) => (None, Some(format!("{:#}", e))),
+    };
+
+    let mut keys = Vec::new();
+    for ka in cert.keys() {
+        let key = ka.key();
+        let (algorithm, bits, curve) = key_algorithm(key);
+
+        // Flags, expiry and revocation come from the binding signature the
+        // policy accepts; without one they are unknown, not absent.
+        let valid_ka = ka.clone().with_policy(&p, None);
+
+        let (caps, expires_at, revoked) = match &valid_ka {
+            Ok(vka) => (
+                vka.key_flags().map(|f| capabilities(&f)).unwrap_or_default(),
+                vka.key_expiration_time().map(to_rfc3339),
+                matches!(vka.revocation_status(), RevocationStatus::Revoked(_)),
+            ),
+            Err(_) => (vec![], None, false),
+        };
+
+        keys.push(KeyComponentDetails {
+            fingerprint: key.fingerprint().to_hex(),
+            key_id: key.keyid().to_hex(),
+            is_primary: ka.primary(),
+            algorithm,
+            bits,
+            curve,
+            capabilities: caps,
+            created_at: to_rfc3339(key.creation_time()),
+            expires_at,
+            revoked,
+            valid: valid_ka.is_ok(),
+            policy_error: valid_ka.err().map(|e| format!("{:#}", e)),
+            has_secret: key.has_secret(),
+        });
+    }
+
+    let issuers: HashMap<openpgp::KeyID, &Cert> =
+        vault_certs.iter().map(|c| (c.keyid(), c)).collect();
+    let primary_uid = vc
+        .as_ref()
+        .and_then(|vc| vc.primary_userid().ok())
+        .map(|ua| ua.userid().clone());
+
+    let mut user_ids = Vec::new();
+    for ua in cert.userids() {
+        let userid = ua.userid();
+        let (valid_ua, ua_policy_error) = match ua.clone().with_policy(&p, None) {
+            Ok(vua) => (Some(vua), None),
+            Err(e) => (None, Some(format!("{:#}", e))),
+        };
+
+        let binding = valid_ua.as_ref().map(|u| u.binding_signature());
+        let list = |names: Option<Vec<String>>| names.unwrap_or_default();
+
+        let preferred_ciphers = list(binding.and_then(|sig| {
+            sig.preferred_symmetric_algorithms()
+                .map(|algos| algos.iter().map(|a| a.to_string()).collect())
+        }));
+        let preferred_hashes = list(binding.and_then(|sig| {
+            sig.preferred_hash_algorithms()
+                .map(|algos| algos.iter().map(|a| a.to_string()).collect())
+        }));
+        let preferred_compression = list(binding.and_then(|sig| {
+            sig.preferred_compression_algorithms()
+                .map(|algos| algos.iter().map(|a| a.to_string()).collect())
+        }));
+
+        let features = binding
+            .and_then(|sig| sig.features())
+            .map(|f| f.as_bitfield().iter_set().map(feature_name).collect())
+            .unwrap_or_default();
+
+        let revoked = valid_ua
+            .as_ref()
+            .map(|u| matches!(u.revocation_status(), RevocationStatus::Revoked(_)))
+            .unwrap_or(false);
+
+        let certifications = ua
+            .certifications()
+            .map(|sig| {
+                let issuer_cert = sig
+                    .get_issuers()
+                    .iter()
+                    .find_map(|h| issuers.get(&openpgp::KeyID::from(h)).copied());
+                let issuer = issuer_cert
+                    .map(|c| c.fingerprint().to_hex())
+                    .or_else(|| sig.get_issuers().first().map(|h| h.to_hex()))
+                    .unwrap_or_default();
+                let issuer_user_id = issuer_cert
+                    .and_then(|c| c.userids().next())
+                    .map(|u| String::from_utf8_lossy(u.userid().value()).to_string());
+                let verified = issuer_cert.map(|c| {
+                    sig.verify_userid_binding(c.primary_key().key(), cert.primary_key().key(), userid)
+                        .is_ok()
+                });
+                let expires_at = sig
+                    .signature_creation_time()
+                    .zip(sig.signature_validity_period())
+                    .map(|(created, period)| to_rfc3339(created + period));
+
+                CertificationDetails {
+                    issuer,
+                    issuer_user_id,
+                    created_at: sig.signature_creation_time().map(to_rfc3339),
+                    expires_at,
+                    trust_depth: sig.trust_signature().map(|(depth, _)| depth),
+                    trust_amount: sig.trust_signature().map(|(_, amount)| amount),
+                    exportable: sig.exportable_certification().unwrap_or(true),
+                    verified,
+                }
+            })
+            .collect();
+
+        user_ids.push(UserIdDetails {
+            user_id: String::from_utf8_lossy(userid.value()).to_string(),
+            is_primary: primary_uid.as_ref() == Some(userid),
+            revoked,
+            preferred_ciphers,
+            preferred_hashes,
+            preferred_compression,
+            features,
+            certifications,
+            policy_error: ua_policy_error,
+        });
+    }
+
+    Ok(KeyDetails {
+        fingerprint: cert.fingerprint().to_hex(),
+        valid_under_policy: vc.is_some(),
+        policy_error,
+        keys,
+        user_ids,
+    })
+}
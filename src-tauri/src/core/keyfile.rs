@@ -0,0 +1,117 @@
+// Detection and extraction of OpenPGP key material from files and free text
+
+use anyhow::Result;
+
+const ARMOR_BEGIN: &str = "-----BEGIN PGP ";
+const ARMOR_END: &str = "-----END PGP ";
+
+/// Magic bytes of a GnuPG keybox (`pubring.kbx`) header blob.
+const KEYBOX_MAGIC: &[u8; 4] = b"KBXf";
+const KEYBOX_BLOB_OPENPGP: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFileFormat {
+    Armored,
+    Binary,
+    Keybox,
+}
+
+/// Guesses the format of key data from its content, not its file extension,
+/// since `.gpg` and `.pgp` files are used for both binary and armored keys.
+pub fn detect_format(data: &[u8]) -> KeyFileFormat {
+    if is_keybox(data) {
+        return KeyFileFormat::Keybox;
+    }
+
+    // Armor can follow any amount of text, e.g. deep inside a saved email.
+    if data.windows(ARMOR_BEGIN.len()).any(|w| w == ARMOR_BEGIN.as_bytes()) {
+        KeyFileFormat::Armored
+    } else {
+        KeyFileFormat::Binary
+    }
+}
+
+/// Splits key data into chunks that can each be fed to the cert parser.
+pub fn key_blocks(data: &[u8]) -> Result<Vec<Vec<u8>>> {
+    match detect_format(data) {
+        KeyFileFormat::Keybox => keybox_keyblocks(data),
+        KeyFileFormat::Armored => Ok(armored_key_blocks(&String::from_utf8_lossy(data))
+            .into_iter()
+            .map(String::into_bytes)
+            .collect()),
+        KeyFileFormat::Binary => Ok(vec![data.to_vec()]),
+    }
+}
+
+/// Finds every armored public or private key block embedded in `text`.
+///
+/// Surrounding text is ignored, so a whole email body can be passed in.
+/// Leading whitespace and `>` quote markers are stripped from each line so
+/// that keys quoted in replies are found as well.
+pub fn armored_key_blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+
+    for line in text.lines() {
+        let line = line.trim_start_matches(|c: char| c == '>' || c.is_whitespace()).trim_end();
+
+        match current.as_mut() {
+            None => {
+                if line.starts_with(ARMOR_BEGIN) && line.contains("KEY BLOCK-----") {
+                    current = Some(vec![line]);
+                }
+            }
+            Some(lines) => {
+                lines.push(line);
+                if line.starts_with(ARMOR_END) {
+                    blocks.push(lines.join("\n") + "\n");
+                    current = None;
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+fn is_keybox(data: &[u8]) -> bool {
+    // The first blob is the header: length, type 1, version, flags, magic.
+    data.len() >= 12 && data[4] == 1 && &data[8..12] == KEYBOX_MAGIC
+}
+
+/// Extracts the OpenPGP keyblocks stored in a GnuPG keybox file.
+///
+/// Each blob starts with its total length and type. OpenPGP blobs store the
+/// offset and length of the raw keyblock relative to the blob start; X.509
+/// and empty blobs are skipped.
+pub fn keybox_keyblocks(data: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let read_u32 = |pos: usize| -> Result<usize> {
+        let bytes = data
+            .get(pos..pos + 4)
+            .ok_or_else(|| anyhow::anyhow!("Truncated keybox file"))?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    };
+
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let blob_len = read_u32(pos)?;
+        if blob_len < 5 || pos + blob_len > data.len() {
+            return Err(anyhow::anyhow!("Corrupt keybox blob at offset {}", pos));
+        }
+
+        if data[pos + 4] == KEYBOX_BLOB_OPENPGP {
+            let offset = read_u32(pos + 8)?;
+            let len = read_u32(pos + 12)?;
+            if offset + len > blob_len {
+                return Err(anyhow::anyhow!("Corrupt keybox keyblock at offset {}", pos));
+            }
+            blocks.push(data[pos + offset..pos + offset + len].to_vec());
+        }
+
+        pos += blob_len;
+    }
+
+    Ok(blocks)
+}
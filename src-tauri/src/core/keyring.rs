@@ -1,13 +1,21 @@
 // Keyring operations for managing PGP keys using SQLite
-use crate::models::KeyMetadata;
+use crate::models::{ImportEntry, ImportReport, ImportStatus, KeyMetadata, UserId};
 use anyhow::Result;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
+use sequoia_openpgp as openpgp;
+use openpgp::cert::CertParser;
+use openpgp::parse::Parse;
+use openpgp::Cert;
 
 pub fn save_key_metadata(
     vault: &crate::core::storage::Vault,
     metadata: &KeyMetadata,
 ) -> Result<()> {
     let conn = vault.conn.lock().unwrap();
+    upsert_metadata(&conn, metadata)
+}
+
+fn upsert_metadata(conn: &rusqlite::Connection, metadata: &KeyMetadata) -> Result<()> {
     let json = serde_json::to_string(metadata)?;
 
     // We update the metadata column.
@@ -69,6 +77,9 @@ pub fn delete_key_files(vault: &crate::core::storage::Vault, fingerprint: &str)
         "DELETE FROM keys WHERE fingerprint = ?1",
         params![fingerprint],
     )?;
+    conn.execute("DELETE FROM key_ids WHERE fingerprint = ?1", params![fingerprint])?;
+    drop(conn);
+    crate::core::trust::invalidate_validity(vault);
     log::info!("Deleted {} rows for fingerprint: {}", count, fingerprint);
     Ok(count > 0)
 }
@@ -79,6 +90,21 @@ pub fn save_key_to_file(
     content: &str,
 ) -> Result<()> {
     let conn = vault.conn.lock().unwrap();
+    let tx = conn.unchecked_transaction()?;
+    upsert_content(&tx, fingerprint, content)?;
+    tx.commit()?;
+    drop(conn);
+    crate::core::trust::invalidate_validity(vault);
+    Ok(())
+}
+
+// Callers run this inside a transaction so the content and its key ID index
+// are written together.
+fn upsert_content(conn: &rusqlite::Connection, fingerprint: &str, content: &str) -> Result<()> {
+    // Parsed before anything is written, so unreadable content is rejected
+    // instead of being stored unindexed.
+    let cert = Cert::from_bytes(content.as_bytes())?;
+
     // Update the key_content column for the existing row (or insert if not exists, though metadata usually comes first)
     // We'll use UPSERT but just update content if key exists, or create a partial row if not
     conn.execute(
@@ -86,5 +112,370 @@ pub fn save_key_to_file(
         ON CONFLICT(fingerprint) DO UPDATE SET key_content=excluded.key_content",
         params![fingerprint, content],
     )?;
+    crate::core::search::index_key_ids(conn, fingerprint, &cert)?;
     Ok(())
 }
+
+pub fn load_key_content(vault: &crate::core::storage::Vault, fingerprint: &str) -> Result<String> {
+    let conn = vault.conn.lock().unwrap();
+    let content: Option<String> = conn
+        .query_row(
+            "SELECT key_content FROM keys WHERE fingerprint = ?1",
+            params![fingerprint],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    content.ok_or_else(|| anyhow::anyhow!("Key not found"))
+}
+
+pub fn load_key_metadata(vault: &crate::core::storage::Vault, fingerprint: &str) -> Result<KeyMetadata> {
+    let conn = vault.conn.lock().unwrap();
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT metadata_json FROM keys WHERE fingerprint = ?1",
+            params![fingerprint],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    let json = json.ok_or_else(|| anyhow::anyhow!("Key not found"))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Loads the metadata of `fingerprint`, applies `update` and stores it again.
+pub fn update_key_metadata<F>(
+    vault: &crate::core::storage::Vault,
+    fingerprint: &str,
+    update: F,
+) -> Result<KeyMetadata>
+where
+    F: FnOnce(&mut KeyMetadata),
+{
+    let mut metadata = load_key_metadata(vault, fingerprint)?;
+    update(&mut metadata);
+    save_key_metadata(vault, &metadata)?;
+    Ok(metadata)
+}
+
+/// Basic parsing of "Name (Comment) <Email>"; every part is optional.
+pub fn parse_userid(s: &str) -> UserId {
+    let (rest, email) = match (s.find('<'), s.rfind('>')) {
+        (Some(start), Some(end)) if start < end => (&s[..start], s[start + 1..end].to_string()),
+        _ => (s, String::new()),
+    };
+
+    let (name, comment) = match (rest.find('('), rest.rfind(')')) {
+        (Some(start), Some(end)) if start < end => (
+            format!("{}{}", &rest[..start], &rest[end + 1..]),
+            Some(rest[start + 1..end].trim().to_string()),
+        ),
+        _ => (rest.to_string(), None),
+    };
+
+    UserId {
+        name: name.trim().to_string(),
+        email,
+        comment,
+    }
+}
+
+/// Builds the metadata row for a cert as it is stored in the vault.
+pub fn metadata_from_cert(cert: &Cert) -> KeyMetadata {
+    let fingerprint = cert.fingerprint().to_string().replace(" ", "");
+
+    // Extract User ID (primary one, falling back to the first one)
+    let p = openpgp::policy::StandardPolicy::new();
+    let primary_uid = cert
+        .with_policy(&p, None)
+        .ok()
+        .and_then(|vc| vc.primary_userid().ok().map(|ua| ua.userid().clone()))
+        .or_else(|| cert.userids().next().map(|ua| ua.userid().clone()));
+    let user_id = primary_uid
+        .map(|uid| parse_userid(&String::from_utf8_lossy(uid.value())))
+        .unwrap_or(UserId {
+            name: String::new(),
+            email: String::new(),
+            comment: None,
+        });
+
+    // Determine key type
+    let key_type = crate::core::inspect::key_type(cert);
+
+    // Use standard policy to check validity and get expiration
+    let expires_at = if let Ok(valid_cert) = cert.with_policy(&p, None) {
+        valid_cert.primary_key().key_expiration_time().map(|t| {
+            let datetime: chrono::DateTime<chrono::Utc> = t.into();
+            datetime.to_rfc3339()
+        })
+    } else {
+        None
+    };
+
+    let creation_time: chrono::DateTime<chrono::Utc> = cert.primary_key().creation_time().into();
+
+    KeyMetadata {
+        fingerprint,
+        key_type,
+        user_id,
+        created_at: creation_time.to_rfc3339(),
+        expires_at,
+        is_private: cert.is_tsk(),
+        is_favorite: false,
+        tags: vec![],
+        notes: None,
+        verified_at: None,
+        protection: crate::core::crypto::protection_status(cert),
+        owner_trust: Default::default(),
+        validity: vec![],
+    }
+}
+
+/// Merges `cert` into the vault.
+///
+/// An existing entry is merged with the incoming cert instead of being
+/// overwritten, so importing the public copy of one of our own keys keeps the
+/// stored secret key material.
+pub fn import_cert(vault: &crate::core::storage::Vault, cert: Cert) -> Result<ImportStatus> {
+    let fingerprint = cert.fingerprint().to_hex();
+
+    let conn = vault.conn.lock().unwrap();
+    let tx = conn.unchecked_transaction()?;
+    let status = merge_into(&tx, &fingerprint, cert)?;
+    tx.commit()?;
+    drop(conn);
+
+    if !matches!(status, ImportStatus::Unchanged) {
+        crate::core::trust::invalidate_validity(vault);
+    }
+    Ok(status)
+}
+
+// Merges `cert` with the stored row for `fingerprint` and writes the result.
+// Callers run this inside a transaction or savepoint so the content and the
+// metadata are written together.
+fn merge_into(conn: &rusqlite::Connection, fingerprint: &str, cert: Cert) -> Result<ImportStatus> {
+    let row: Option<(Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT key_content, metadata_json FROM keys WHERE fingerprint = ?1",
+            params![fingerprint],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let (content, json) = row.unwrap_or_default();
+    let existing = content.map(|c| Cert::from_bytes(c.as_bytes())).transpose()?;
+    let previous = json.and_then(|j| serde_json::from_str(&j).ok());
+
+    match merge_cert(existing, previous, cert)? {
+        Some((status, content, metadata)) => {
+            upsert_content(conn, fingerprint, &content)?;
+            upsert_metadata(conn, &metadata)?;
+            Ok(status)
+        }
+        None => Ok(ImportStatus::Unchanged),
+    }
+}
+
+/// Merges many certs into the vault in a single transaction, e.g. a whole
+/// GnuPG keyring. Certs sharing a fingerprint, such as the public and the
+/// secret export of one key, are merged with each other first.
+pub fn import_certs(vault: &crate::core::storage::Vault, certs: Vec<Cert>) -> Result<ImportReport> {
+    let mut report = ImportReport::default();
+    let mut merged: Vec<Cert> = Vec::new();
+    let mut index: std::collections::HashMap<openpgp::Fingerprint, usize> = std::collections::HashMap::new();
+    for cert in certs {
+        match index.get(&cert.fingerprint()) {
+            // A copy that can't be merged is reported on its own; the
+            // copies merged so far are still imported.
+            Some(&i) => match merged[i].clone().merge_public_and_secret(cert.clone()) {
+                Ok(m) => merged[i] = m,
+                Err(e) => {
+                    log::error!("Failed to merge copies of key {}: {}", cert.fingerprint(), e);
+                    report.push(ImportEntry {
+                        fingerprint: Some(cert.fingerprint().to_hex()),
+                        user_id: cert
+                            .userids()
+                            .next()
+                            .map(|uid| String::from_utf8_lossy(uid.userid().value()).to_string()),
+                        status: ImportStatus::Failed,
+                        error: Some(e.to_string()),
+                    });
+                }
+            },
+            None => {
+                index.insert(cert.fingerprint(), merged.len());
+                merged.push(cert);
+            }
+        }
+    }
+
+    let mut conn = vault.conn.lock().unwrap();
+    let mut tx = conn.transaction()?;
+
+    for cert in merged {
+        let fingerprint = cert.fingerprint().to_hex();
+        let user_id = cert
+            .userids()
+            .next()
+            .map(|uid| String::from_utf8_lossy(uid.userid().value()).to_string());
+
+        // Each cert gets a savepoint, so a failed one leaves nothing behind.
+        let result = (|| -> Result<ImportStatus> {
+            let sp = tx.savepoint()?;
+            let status = merge_into(&sp, &fingerprint, cert)?;
+            sp.commit()?;
+            Ok(status)
+        })();
+
+        report.push(match result {
+            Ok(status) => ImportEntry {
+                fingerprint: Some(fingerprint),
+                user_id,
+                status,
+                error: None,
+            },
+            Err(e) => {
+                log::error!("Failed to import key {}: {}", fingerprint, e);
+                ImportEntry {
+                    fingerprint: Some(fingerprint),
+                    user_id,
+                    status: ImportStatus::Failed,
+                    error: Some(e.to_string()),
+                }
+            }
+        });
+    }
+
+    tx.commit()?;
+    drop(conn);
+    crate::core::trust::invalidate_validity(vault);
+    Ok(report)
+}
+
+// Merges `cert` into the stored `existing` cert. Returns the status, the
+// armored content and the metadata to store, or None if nothing changed.
+fn merge_cert(
+    existing: Option<Cert>,
+    previous: Option<KeyMetadata>,
+    cert: Cert,
+) -> Result<Option<(ImportStatus, String, KeyMetadata)>> {
+    let (merged, status) = match existing {
+        Some(existing) => {
+            let merged = existing.clone().merge_public_and_secret(cert)?;
+            // Cert equality ignores secret key material, so newly added
+            // secrets have to be checked for separately.
+            if merged == existing && merged.keys().secret().count() == existing.keys().secret().count() {
+                return Ok(None);
+            }
+            (merged, ImportStatus::Updated)
+        }
+        None => (cert, ImportStatus::New),
+    };
+
+    let content = if merged.is_tsk() {
+        crate::core::crypto::armor_tsk(&merged)?
+    } else {
+        crate::core::crypto::armor_public(&merged)?
+    };
+
+    // Favorite, tags, notes and the verification date are user data, not
+    // part of the cert; keep them when an existing key is updated.
+    let mut metadata = metadata_from_cert(&merged);
+    if let Some(previous) = previous {
+        metadata.is_favorite = previous.is_favorite;
+        metadata.tags = previous.tags;
+        metadata.notes = previous.notes;
+        metadata.verified_at = previous.verified_at;
+    }
+
+    Ok(Some((status, content, metadata)))
+}
+
+/// Imports every cert found in `data`, which may be a single key or a whole
+/// keyring export, and reports the outcome per fingerprint.
+pub fn import_keyring(vault: &crate::core::storage::Vault, data: &[u8]) -> Result<ImportReport> {
+    let mut report = ImportReport::default();
+
+    for parsed in CertParser::from_bytes(data)? {
+        let entry = match parsed {
+            Ok(cert) => {
+                let fingerprint = cert.fingerprint().to_hex();
+                let user_id = cert
+                    .userids()
+                    .next()
+                    .map(|uid| String::from_utf8_lossy(uid.userid().value()).to_string());
+
+                match import_cert(vault, cert) {
+                    Ok(status) => ImportEntry {
+                        fingerprint: Some(fingerprint),
+                        user_id,
+                        status,
+                        error: None,
+                    },
+                    Err(e) => {
+                        log::error!("Failed to import key {}: {}", fingerprint, e);
+                        ImportEntry {
+                            fingerprint: Some(fingerprint),
+                            user_id,
+                            status: ImportStatus::Failed,
+                            error: Some(e.to_string()),
+                        }
+                    }
+                }
+            }
+            Err(e) => ImportEntry {
+                fingerprint: None,
+                user_id: None,
+                status: ImportStatus::Failed,
+                error: Some(e.to_string()),
+            },
+        };
+
+        report.push(entry);
+    }
+
+    if report.entries.is_empty() {
+        return Err(anyhow::anyhow!("No OpenPGP keys found in input"));
+    }
+
+    Ok(report)
+}
+
+/// Imports key data of any supported format: armored text (including key
+/// blocks embedded in free text such as an email body), binary keyrings and
+/// GnuPG keybox files.
+pub fn import_key_data(vault: &crate::core::storage::Vault, data: &[u8]) -> Result<ImportReport> {
+    let blocks = crate::core::keyfile::key_blocks(data)?;
+    if blocks.is_empty() {
+        return Err(anyhow::anyhow!("No OpenPGP keys found in input"));
+    }
+
+    let mut report = ImportReport::default();
+    for block in blocks {
+        match import_keyring(vault, &block) {
+            Ok(block_report) => report.extend(block_report),
+            Err(e) => report.push(ImportEntry {
+                fingerprint: None,
+                user_id: None,
+                status: ImportStatus::Failed,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Returns `(fingerprint, key_content)` for every key stored in the vault.
+pub fn load_all_key_contents(vault: &crate::core::storage::Vault) -> Result<Vec<(String, String)>> {
+    let conn = vault.conn.lock().unwrap();
+    let mut stmt = conn.prepare("SELECT fingerprint, key_content FROM keys WHERE key_content IS NOT NULL")?;
+
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+    let mut keys = Vec::new();
+    for row in rows {
+        keys.push(row?);
+    }
+    Ok(keys)
+}
@@ -0,0 +1,618 @@
+// HKP and VKS keyserver client
+//
+// Only used when keyserver access is enabled in the config, and only for
+// lookups and uploads the user starts. HKP is the classic `/pks/` interface
+// (SKS, Hockeypuck); VKS the verifying interface of keys.openpgp.org-style
+// servers, which only looks keys up by exact email, fingerprint or key ID.
+use crate::core::config::{KeyserverConfig, KeyserverProtocol};
+use crate::models::{ImportEntry, ImportStatus, KeyserverAddressStatus, KeyserverKey, KeyserverUpload};
+use anyhow::{anyhow, Result};
+use reqwest::{StatusCode, Url};
+use sequoia_openpgp as openpgp;
+use openpgp::parse::Parse;
+use std::time::Duration;
+
+const HKP_DEFAULT_PORT: u16 = 11371;
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct Keyserver {
+    base: Url,
+    protocol: KeyserverProtocol,
+    client: reqwest::Client,
+}
+
+/// Keys fetched from a keyserver. Certs that don't match the query are
+/// dropped and reported as failed imports, as GnuPG does.
+pub struct FetchedKeys {
+    pub certs: Vec<openpgp::Cert>,
+    pub rejected: Vec<ImportEntry>,
+}
+
+/// What a search or fetch is for, derived from the user's query.
+enum Query {
+    Fingerprint(String),
+    KeyId(String),
+    Email(String),
+    Text(String),
+}
+
+impl Query {
+    fn parse(query: &str) -> Result<Self> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(anyhow!("Search query is empty"));
+        }
+
+        let hex: String = query
+            .trim_start_matches("0x")
+            .trim_start_matches("0X")
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        if hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            match hex.len() {
+                40 | 64 => return Ok(Query::Fingerprint(hex.to_uppercase())),
+                16 => return Ok(Query::KeyId(hex.to_uppercase())),
+                _ => {}
+            }
+        }
+
+        let email = query.trim_start_matches('<').trim_end_matches('>');
+        if email.contains('@') && !email.contains(char::is_whitespace) {
+            return Ok(Query::Email(email.to_string()));
+        }
+        Ok(Query::Text(query.to_string()))
+    }
+
+    // HKP takes hex handles with a 0x prefix and anything else verbatim.
+    fn hkp_search(&self) -> String {
+        match self {
+            Query::Fingerprint(h) | Query::KeyId(h) => format!("0x{}", h),
+            Query::Email(s) | Query::Text(s) => s.clone(),
+        }
+    }
+
+    // Whether `cert` is what an exact query asked for: one of its keys has
+    // the fingerprint or key ID, or one of its user IDs the address. Text
+    // queries are fuzzy and accept anything.
+    fn matches(&self, cert: &openpgp::Cert) -> bool {
+        match self {
+            Query::Fingerprint(f) => cert.keys().any(|ka| &ka.key().fingerprint().to_hex() == f),
+            Query::KeyId(k) => cert.keys().any(|ka| &ka.key().keyid().to_hex() == k),
+            Query::Email(e) => cert
+                .userids()
+                .any(|ua| matches!(ua.userid().email2(), Ok(Some(a)) if a.eq_ignore_ascii_case(e))),
+            Query::Text(_) => true,
+        }
+    }
+}
+
+impl Keyserver {
+    /// Connects to the configured keyserver, failing if access is disabled.
+    pub fn from_config(config: &KeyserverConfig) -> Result<Self> {
+        if !config.enabled {
+            return Err(anyhow!("Keyserver access is disabled in the settings"));
+        }
+        let url = config
+            .url
+            .as_deref()
+            .filter(|u| !u.trim().is_empty())
+            .ok_or_else(|| anyhow!("No keyserver URL is configured"))?;
+        Self::new(url, config.protocol)
+    }
+
+    /// Accepts `hkp://` (port 11371 unless given), `hkps://`, `http://` and
+    /// `https://` URLs.
+    pub fn new(url: &str, protocol: KeyserverProtocol) -> Result<Self> {
+        let url = url.trim().trim_end_matches('/');
+        let base = if let Some(rest) = url.strip_prefix("hkp://") {
+            let mut base = Url::parse(&format!("http://{}", rest))?;
+            if base.port().is_none() {
+                base.set_port(Some(HKP_DEFAULT_PORT))
+                    .map_err(|_| anyhow!("Invalid keyserver URL: {}", url))?;
+            }
+            base
+        } else if let Some(rest) = url.strip_prefix("hkps://") {
+            Url::parse(&format!("https://{}", rest))?
+        } else if url.starts_with("http://") || url.starts_with("https://") {
+            Url::parse(url)?
+        } else {
+            return Err(anyhow!("Unsupported keyserver URL: {}", url));
+        };
+
+        let client = reqwest::Client::builder().timeout(TIMEOUT).build()?;
+        Ok(Self { base, protocol, client })
+    }
+
+    // Resolves `segments` below the base URL, keeping any path prefix the
+    // server is mounted under. Segments are percent-encoded.
+    fn endpoint(&self, segments: &[&str]) -> Result<Url> {
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid keyserver URL: {}", self.base))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    /// Lists the keys matching `query`. VKS servers have no index, so the
+    /// matching key is fetched and described instead.
+    pub async fn search(&self, query: &str) -> Result<Vec<KeyserverKey>> {
+        let query = Query::parse(query)?;
+        match self.protocol {
+            KeyserverProtocol::Hkp => {
+                let url = self.endpoint(&["pks", "lookup"])?;
+                let response = self
+                    .client
+                    .get(url)
+                    .query(&[("op", "index"), ("options", "mr"), ("search", &query.hkp_search())])
+                    .send()
+                    .await?;
+                match response.status() {
+                    StatusCode::NOT_FOUND => Ok(Vec::new()),
+                    status if status.is_success() => Ok(parse_hkp_index(&response.text().await?)),
+                    status => Err(anyhow!("Keyserver returned {}", status)),
+                }
+            }
+            KeyserverProtocol::Vks => match self.fetch_query(&query).await? {
+                Some(data) => openpgp::cert::CertParser::from_bytes(&data)?
+                    .map(|cert| Ok(describe_cert(&cert?)))
+                    .collect(),
+                None => Ok(Vec::new()),
+            },
+        }
+    }
+
+    /// Fetches the keys matching `query`, usually a fingerprint picked from
+    /// the search results. Any secret key material a server sends along is
+    /// dropped, and so are certs the query didn't ask for.
+    pub async fn fetch(&self, query: &str) -> Result<FetchedKeys> {
+        let query = Query::parse(query)?;
+        let data = self
+            .fetch_query(&query)
+            .await?
+            .ok_or_else(|| anyhow!("No key found on the keyserver"))?;
+
+        let certs = openpgp::cert::CertParser::from_bytes(&data)?
+            .map(|cert| cert.map(|c| c.strip_secret_key_material()))
+            .collect::<openpgp::Result<Vec<_>>>()?;
+        if certs.is_empty() {
+            return Err(anyhow!("The keyserver returned no keys"));
+        }
+
+        let mut fetched = FetchedKeys {
+            certs: Vec::new(),
+            rejected: Vec::new(),
+        };
+        for cert in certs {
+            if query.matches(&cert) {
+                fetched.certs.push(cert);
+                continue;
+            }
+            log::warn!("Keyserver returned key {} which does not match the query", cert.fingerprint());
+            fetched.rejected.push(ImportEntry {
+                fingerprint: Some(cert.fingerprint().to_hex()),
+                user_id: cert
+                    .userids()
+                    .next()
+                    .map(|uid| String::from_utf8_lossy(uid.userid().value()).to_string()),
+                status: ImportStatus::Failed,
+                error: Some("Rejected: the key does not match the query".to_string()),
+            });
+        }
+        Ok(fetched)
+    }
+
+    async fn fetch_query(&self, query: &Query) -> Result<Option<Vec<u8>>> {
+        let request = match self.protocol {
+            KeyserverProtocol::Hkp => self
+                .client
+                .get(self.endpoint(&["pks", "lookup"])?)
+                .query(&[("op", "get"), ("options", "mr"), ("search", &query.hkp_search())]),
+            KeyserverProtocol::Vks => {
+                let url = match query {
+                    Query::Fingerprint(f) => self.endpoint(&["vks", "v1", "by-fingerprint", f])?,
+                    Query::KeyId(k) => self.endpoint(&["vks", "v1", "by-keyid", k])?,
+                    Query::Email(e) => self.endpoint(&["vks", "v1", "by-email", e])?,
+                    Query::Text(_) => {
+                        return Err(anyhow!(
+                            "VKS keyservers only look up keys by email address, fingerprint or key ID"
+                        ))
+                    }
+                };
+                self.client.get(url)
+            }
+        };
+
+        let response = request.send().await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(response.bytes().await?.to_vec())),
+            status => Err(anyhow!("Keyserver returned {}", status)),
+        }
+    }
+
+    /// Uploads an armored public key.
+    pub async fn upload(&self, fingerprint: &str, armored: &str) -> Result<KeyserverUpload> {
+        match self.protocol {
+            KeyserverProtocol::Hkp => {
+                let response = self
+                    .client
+                    .post(self.endpoint(&["pks", "add"])?)
+                    .form(&[("keytext", armored)])
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    return Err(anyhow!("Keyserver returned {}", response.status()));
+                }
+                Ok(KeyserverUpload {
+                    fingerprint: fingerprint.to_string(),
+                    addresses: Vec::new(),
+                })
+            }
+            KeyserverProtocol::Vks => {
+                let response = self
+                    .client
+                    .post(self.endpoint(&["vks", "v1", "upload"])?)
+                    .json(&serde_json::json!({ "keytext": armored }))
+                    .send()
+                    .await?;
+                let status = response.status();
+                let body: serde_json::Value = response.json().await.unwrap_or_default();
+                if !status.is_success() {
+                    let error = body["error"].as_str().unwrap_or_default();
+                    return Err(anyhow!("Keyserver returned {} {}", status, error));
+                }
+
+                let mut addresses: Vec<KeyserverAddressStatus> = body["status"]
+                    .as_object()
+                    .map(|status| {
+                        status
+                            .iter()
+                            .map(|(address, state)| KeyserverAddressStatus {
+                                address: address.clone(),
+                                status: state.as_str().unwrap_or_default().to_string(),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                addresses.sort_by(|a, b| a.address.cmp(&b.address));
+
+                Ok(KeyserverUpload {
+                    fingerprint: body["key_fpr"].as_str().unwrap_or(fingerprint).to_string(),
+                    addresses,
+                })
+            }
+        }
+    }
+}
+
+/// Parses a machine readable HKP index (`options=mr`): `pub:` lines start a
+/// key, the `uid:` lines following belong to it.
+pub fn parse_hkp_index(text: &str) -> Vec<KeyserverKey> {
+    let mut keys: Vec<KeyserverKey> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        let fields: Vec<&str> = line.split(':').collect();
+        match fields[0] {
+            "pub" if fields.len() > 1 => {
+                let field = |i: usize| fields.get(i).copied().filter(|f| !f.is_empty());
+                keys.push(KeyserverKey {
+                    key_handle: fields[1].to_uppercase(),
+                    algorithm: field(2)
+                        .and_then(|a| a.parse::<u8>().ok())
+                        .map(|a| openpgp::types::PublicKeyAlgorithm::from(a).to_string()),
+                    bits: field(3).and_then(|b| b.parse().ok()),
+                    created_at: field(4).and_then(timestamp),
+                    expires_at: field(5).and_then(timestamp),
+                    revoked: field(6).is_some_and(|f| f.contains('r')),
+                    user_ids: Vec::new(),
+                });
+            }
+            "uid" if fields.len() > 1 => {
+                if let Some(key) = keys.last_mut() {
+                    key.user_ids.push(percent_decode(fields[1]));
+                }
+            }
+            _ => {}
+        }
+    }
+    keys
+}
+
+// HKP indexes give Unix timestamps in seconds.
+fn timestamp(s: &str) -> Option<String> {
+    let secs = s.parse::<i64>().ok()?;
+    chrono::DateTime::from_timestamp(secs, 0).map(|t| t.to_rfc3339())
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn describe_cert(cert: &openpgp::Cert) -> KeyserverKey {
+    let metadata = crate::core::keyring::metadata_from_cert(cert);
+    let p = openpgp::policy::StandardPolicy::new();
+    let revoked = matches!(
+        cert.revocation_status(&p, None),
+        openpgp::types::RevocationStatus::Revoked(_)
+    );
+    KeyserverKey {
+        key_handle: metadata.fingerprint,
+        algorithm: Some(cert.primary_key().pk_algo().to_string()),
+        bits: cert.primary_key().mpis().bits(),
+        created_at: Some(metadata.created_at),
+        expires_at: metadata.expires_at,
+        revoked,
+        user_ids: cert
+            .userids()
+            .map(|ua| String::from_utf8_lossy(ua.userid().value()).to_string())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openpgp::cert::CertBuilder;
+    use openpgp::serialize::SerializeInto;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Serves `responses` (path substring, body) on a local port, one request
+    /// per connection, and records every request line and body it receives.
+    /// Unmatched requests get a 404.
+    fn mock_server(responses: Vec<(&'static str, Vec<u8>)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let target = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+                seen.lock()
+                    .unwrap()
+                    .push(format!("{} {}", target, String::from_utf8_lossy(&body)));
+
+                let (status, body) = match responses.iter().find(|(path, _)| target.contains(path)) {
+                    Some((_, body)) => ("200 OK", body.clone()),
+                    None => ("404 Not Found", Vec::new()),
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn alice() -> openpgp::Cert {
+        CertBuilder::general_purpose(None, Some("Alice <alice@example.org>"))
+            .generate()
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn query_parse() {
+        let fpr = "0123456789ABCDEF0123456789ABCDEF01234567";
+        assert!(matches!(Query::parse(&format!("0x{}", fpr.to_lowercase())), Ok(Query::Fingerprint(f)) if f == fpr));
+        assert!(matches!(Query::parse("0123 4567 89AB CDEF 0123  4567 89AB CDEF 0123 4567"), Ok(Query::Fingerprint(f)) if f == fpr));
+        assert!(matches!(Query::parse("89abcdef01234567"), Ok(Query::KeyId(k)) if k == "89ABCDEF01234567"));
+        assert!(matches!(Query::parse(" <alice@example.org> "), Ok(Query::Email(e)) if e == "alice@example.org"));
+        assert!(matches!(Query::parse("Alice Example"), Ok(Query::Text(t)) if t == "Alice Example"));
+        // Hex of any other length is just text.
+        assert!(matches!(Query::parse("cafe"), Ok(Query::Text(t)) if t == "cafe"));
+        assert!(Query::parse("  ").is_err());
+
+        assert_eq!(Query::parse("89abcdef01234567").unwrap().hkp_search(), "0x89ABCDEF01234567");
+        assert_eq!(Query::parse("<alice@example.org>").unwrap().hkp_search(), "alice@example.org");
+    }
+
+    #[test]
+    fn hkp_index() {
+        let index = "info:1:2\n\
+                     uid:Orphan:1700000000::\n\
+                     pub:0123456789abcdef0123456789abcdef01234567:22:255:1700000000:1800000000:\n\
+                     uid:Alice%20%3Calice@example.org%3E:1700000000::\n\
+                     uid:Alice (work) %3Calice@work.example%3E:1700000000::\n\
+                     pub:89ABCDEF01234567:1:4096:1600000000::r\n";
+        let keys = parse_hkp_index(index);
+        assert_eq!(keys.len(), 2);
+
+        assert_eq!(keys[0].key_handle, "0123456789ABCDEF0123456789ABCDEF01234567");
+        assert_eq!(keys[0].bits, Some(255));
+        assert_eq!(keys[0].created_at.as_deref(), Some("2023-11-14T22:13:20+00:00"));
+        assert!(keys[0].expires_at.is_some());
+        assert!(!keys[0].revoked);
+        assert_eq!(keys[0].user_ids, vec!["Alice <alice@example.org>", "Alice (work) <alice@work.example>"]);
+
+        assert_eq!(keys[1].key_handle, "89ABCDEF01234567");
+        assert_eq!(keys[1].bits, Some(4096));
+        assert_eq!(keys[1].expires_at, None);
+        assert!(keys[1].revoked);
+        assert!(keys[1].user_ids.is_empty());
+
+        assert!(parse_hkp_index("info:1:0\n").is_empty());
+    }
+
+    #[test]
+    fn hkp_urls() {
+        let server = Keyserver::new("hkp://keys.example.org", KeyserverProtocol::Hkp).unwrap();
+        assert_eq!(server.base.as_str(), "http://keys.example.org:11371/");
+        let server = Keyserver::new("hkps://keys.example.org/", KeyserverProtocol::Hkp).unwrap();
+        assert_eq!(server.base.as_str(), "https://keys.example.org/");
+        let server = Keyserver::new("https://example.org/keys", KeyserverProtocol::Vks).unwrap();
+        assert_eq!(
+            server.endpoint(&["vks", "v1", "by-email", "a b@example.org"]).unwrap().as_str(),
+            "https://example.org/keys/vks/v1/by-email/a%20b@example.org"
+        );
+        assert!(Keyserver::new("ldap://keys.example.org", KeyserverProtocol::Hkp).is_err());
+        assert!(Keyserver::from_config(&KeyserverConfig::default()).is_err());
+    }
+
+    #[tokio::test]
+    async fn hkp_search_fetch_upload() {
+        let cert = alice();
+        let fpr = cert.fingerprint().to_hex();
+        let index = format!("info:1:1\npub:{}:22::1700000000::\nuid:Alice%20%3Calice@example.org%3E:1700000000::\n", fpr);
+        let (url, requests) = mock_server(vec![
+            ("op=index", index.into_bytes()),
+            // A careless server handing out secret keys.
+            ("op=get", cert.as_tsk().armored().to_vec().unwrap()),
+            ("/pks/add", Vec::new()),
+        ]);
+        let server = Keyserver::new(&url, KeyserverProtocol::Hkp).unwrap();
+
+        let keys = server.search("alice@example.org").await.unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].key_handle, fpr);
+        assert_eq!(keys[0].user_ids, vec!["Alice <alice@example.org>"]);
+
+        let fetched = server.fetch(&fpr).await.unwrap();
+        assert!(fetched.rejected.is_empty());
+        assert_eq!(fetched.certs.len(), 1);
+        assert_eq!(fetched.certs[0].fingerprint(), cert.fingerprint());
+        assert!(!fetched.certs[0].is_tsk());
+
+        let armored = String::from_utf8(cert.armored().to_vec().unwrap()).unwrap();
+        let upload = server.upload(&fpr, &armored).await.unwrap();
+        assert_eq!(upload.fingerprint, fpr);
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("/pks/lookup?op=index&options=mr&search=alice%40example.org"));
+        assert!(requests[1].starts_with(&format!("/pks/lookup?op=get&options=mr&search=0x{}", fpr)));
+        assert!(requests[2].starts_with("/pks/add keytext=-----BEGIN+PGP+PUBLIC+KEY+BLOCK-----"));
+    }
+
+    #[tokio::test]
+    async fn hkp_not_found() {
+        let (url, _) = mock_server(Vec::new());
+        let server = Keyserver::new(&url, KeyserverProtocol::Hkp).unwrap();
+        assert!(server.search("nobody@example.org").await.unwrap().is_empty());
+        assert!(server.fetch("nobody@example.org").await.is_err());
+        assert!(server.upload("", "").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn vks_search_fetch_upload() {
+        let cert = alice();
+        let fpr = cert.fingerprint().to_hex();
+        let public = cert.armored().to_vec().unwrap();
+        let status = format!(
+            "{{\"key_fpr\":\"{}\",\"token\":\"t\",\"status\":{{\"alice@example.org\":\"unpublished\"}}}}",
+            fpr
+        );
+        let (url, requests) = mock_server(vec![
+            ("/vks/v1/by-email/alice@example.org", public.clone()),
+            ("/vks/v1/by-fingerprint/", public),
+            ("/vks/v1/upload", status.into_bytes()),
+        ]);
+        let server = Keyserver::new(&url, KeyserverProtocol::Vks).unwrap();
+
+        let keys = server.search("<alice@example.org>").await.unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].key_handle, fpr);
+        assert_eq!(keys[0].user_ids, vec!["Alice <alice@example.org>"]);
+        assert!(server.search("bob@example.org").await.unwrap().is_empty());
+        // VKS has no full text search.
+        assert!(server.search("Alice").await.is_err());
+
+        let fetched = server.fetch(&fpr).await.unwrap();
+        assert_eq!(fetched.certs.len(), 1);
+        assert_eq!(fetched.certs[0].fingerprint(), cert.fingerprint());
+
+        let armored = String::from_utf8(cert.armored().to_vec().unwrap()).unwrap();
+        let upload = server.upload(&fpr, &armored).await.unwrap();
+        assert_eq!(upload.fingerprint, fpr);
+        assert_eq!(upload.addresses.len(), 1);
+        assert_eq!(upload.addresses[0].address, "alice@example.org");
+        assert_eq!(upload.addresses[0].status, "unpublished");
+
+        let requests = requests.lock().unwrap();
+        assert!(requests.iter().any(|r| r.starts_with(&format!("/vks/v1/by-fingerprint/{} ", fpr.to_uppercase()))));
+        assert!(requests.iter().any(|r| r.starts_with("/vks/v1/upload {\"keytext\":")));
+    }
+
+    #[tokio::test]
+    async fn fetch_drops_unrequested_keys() {
+        let cert = alice();
+        let mallory = CertBuilder::general_purpose(None, Some("Mallory <mallory@example.org>"))
+            .generate()
+            .unwrap()
+            .0;
+        let fpr = cert.fingerprint().to_hex();
+        let mut keyring = cert.armored().to_vec().unwrap();
+        keyring.extend(mallory.armored().to_vec().unwrap());
+        let (url, _) = mock_server(vec![("/pks/lookup", keyring)]);
+        let server = Keyserver::new(&url, KeyserverProtocol::Hkp).unwrap();
+
+        let queries = [
+            fpr.clone(),
+            cert.keyid().to_hex(),
+            // Subkeys count too.
+            cert.keys().subkeys().next().unwrap().key().fingerprint().to_hex(),
+            "<ALICE@example.org>".to_string(),
+        ];
+        for query in queries {
+            let fetched = server.fetch(&query).await.unwrap();
+            assert_eq!(fetched.certs.len(), 1, "{}", query);
+            assert_eq!(fetched.certs[0].fingerprint(), cert.fingerprint());
+            assert_eq!(fetched.rejected.len(), 1);
+            assert_eq!(fetched.rejected[0].fingerprint, Some(mallory.fingerprint().to_hex()));
+            assert_eq!(fetched.rejected[0].user_id.as_deref(), Some("Mallory <mallory@example.org>"));
+            assert!(matches!(fetched.rejected[0].status, ImportStatus::Failed));
+        }
+
+        // Nothing to match a text search against.
+        assert_eq!(server.fetch("Alice").await.unwrap().certs.len(), 2);
+
+        let fetched = server.fetch("bob@example.org").await.unwrap();
+        assert!(fetched.certs.is_empty());
+        assert_eq!(fetched.rejected.len(), 2);
+    }
+}
@@ -0,0 +1,331 @@
+// Printable key information sheets
+//
+// One page per key for key-signing events and onboarding packs: the user IDs,
+// the fingerprint in the forms people compare it in, the keys' algorithms and
+// lifetimes, and a QR code of the public key. Keys too large for a QR code
+// that still scans from paper get the `openpgp4fpr:` URI of their fingerprint
+// instead.
+use crate::core::{crypto, fingerprint, inspect, pdf};
+use crate::models::{KeyComponentDetails, KeyMetadata};
+use anyhow::Result;
+use qrcode::render::svg;
+use qrcode::{Color, EcLevel, QrCode, Version};
+use sequoia_openpgp as openpgp;
+use openpgp::Cert;
+
+// Version 30 is 137 modules wide, still about 0.6 mm per module at QR_SIZE.
+// That holds a minimized Ed25519 key with a few user IDs; most RSA keys get
+// the fingerprint.
+const MAX_QR_VERSION: i16 = 30;
+const QR_SIZE: f32 = 240.0; // Points, including the quiet zone
+const QR_QUIET_ZONE: usize = 4; // Modules
+// Keeps the text above the QR code
+const MAX_USER_IDS: usize = 8;
+const MAX_KEYS: usize = 6;
+const MAX_LINE_CHARS: usize = 90;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QrContent {
+    PublicKey,
+    Fingerprint,
+}
+
+impl QrContent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QrContent::PublicKey => "public_key",
+            QrContent::Fingerprint => "fingerprint",
+        }
+    }
+}
+
+/// Everything printed on a key sheet, ready for `to_pdf` or `to_html`.
+pub struct KeySheet {
+    pub title: String,
+    pub user_ids: Vec<String>,
+    pub fingerprint: String, // Spaced hex
+    pub words: Vec<String>,
+    pub uri: String,
+    pub randomart: String,
+    pub keys: Vec<String>, // One line per valid key, primary key first
+    pub generated: String,
+    pub qr_content: QrContent,
+    qr: QrCode,
+}
+
+/// Collects the sheet for `cert`. `qr_content` is what the QR code should
+/// hold; a public key that does not fit falls back to the fingerprint URI.
+pub fn build(cert: &Cert, metadata: &KeyMetadata, qr_content: QrContent) -> Result<KeySheet> {
+    let details = inspect::key_details(cert, &[])?;
+    let fp = cert.fingerprint();
+
+    let user_ids: Vec<String> = details
+        .user_ids
+        .iter()
+        .filter(|u| !u.revoked)
+        .map(|u| u.user_id.clone())
+        .collect();
+    let title = details
+        .user_ids
+        .iter()
+        .find(|u| u.is_primary)
+        .or_else(|| details.user_ids.first())
+        .map(|u| u.user_id.clone())
+        .unwrap_or_else(|| fp.to_hex());
+
+    let keys = details
+        .keys
+        .iter()
+        .filter(|k| k.valid && !k.revoked)
+        .map(describe_key)
+        .collect();
+
+    let uri = fingerprint::uri(&fp);
+    let public_key = match qr_content {
+        // A key the policy can't minimize gets the fingerprint code, just
+        // like one that is too large.
+        QrContent::PublicKey => crypto::minimize_cert(cert)
+            .and_then(|minimal| crypto::serialize_public_keyring(&[minimal], true))
+            .map_err(|e| log::warn!("Key {} not minimized for the key sheet: {}", fp, e))
+            .ok()
+            .and_then(|armored| QrCode::with_error_correction_level(&armored, EcLevel::L).ok())
+            .filter(fits),
+        QrContent::Fingerprint => None,
+    };
+    let (qr_content, qr) = match public_key {
+        Some(code) => (QrContent::PublicKey, code),
+        None => (
+            QrContent::Fingerprint,
+            QrCode::with_error_correction_level(uri.as_bytes(), EcLevel::M)?,
+        ),
+    };
+
+    Ok(KeySheet {
+        title,
+        user_ids,
+        fingerprint: fp.to_spaced_hex(),
+        words: fingerprint::words(&fp).into_iter().map(String::from).collect(),
+        uri,
+        randomart: fingerprint::randomart(&fp, &metadata.key_type.to_string()),
+        keys,
+        generated: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+        qr_content,
+        qr,
+    })
+}
+
+fn fits(code: &QrCode) -> bool {
+    matches!(code.version(), Version::Normal(v) if v <= MAX_QR_VERSION)
+}
+
+// "Subkey: ECDH Curve25519 - encrypt - created 2024-01-01, expires never"
+fn describe_key(key: &KeyComponentDetails) -> String {
+    let algorithm = match (&key.curve, key.bits) {
+        (Some(curve), _) => format!("{} {}", key.algorithm, curve),
+        (None, Some(bits)) => format!("{}, {} bits", key.algorithm, bits),
+        (None, None) => key.algorithm.clone(),
+    };
+
+    let mut caps: Vec<&str> = Vec::new();
+    for cap in &key.capabilities {
+        let cap = if cap.starts_with("encrypt") { "encrypt" } else { cap.as_str() };
+        if !caps.contains(&cap) {
+            caps.push(cap);
+        }
+    }
+
+    format!(
+        "{}: {} - {} - created {}, expires {}",
+        if key.is_primary { "Primary key" } else { "Subkey" },
+        algorithm,
+        if caps.is_empty() { "no usage".to_string() } else { caps.join(", ") },
+        date(&key.created_at),
+        key.expires_at.as_deref().map(date).unwrap_or_else(|| "never".to_string())
+    )
+}
+
+fn date(rfc3339: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(rfc3339)
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|_| rfc3339.to_string())
+}
+
+fn truncate(s: &str) -> String {
+    if s.chars().count() <= MAX_LINE_CHARS {
+        s.to_string()
+    } else {
+        format!("{}...", s.chars().take(MAX_LINE_CHARS - 3).collect::<String>())
+    }
+}
+
+// Lists past the limit end in an "... and N more" line.
+fn capped(items: &[String], max: usize) -> Vec<String> {
+    let mut lines: Vec<String> = items.iter().take(max).map(|s| truncate(s)).collect();
+    if items.len() > max {
+        lines.push(format!("... and {} more", items.len() - max));
+    }
+    lines
+}
+
+fn qr_caption(content: QrContent) -> &'static str {
+    match content {
+        QrContent::PublicKey => "QR code: public key",
+        QrContent::Fingerprint => "QR code: fingerprint (openpgp4fpr URI)",
+    }
+}
+
+const CHECK_NOTE: &str = "Compare the fingerprint with the key owner in person before certifying this key.";
+
+impl KeySheet {
+    /// Lays the sheet out on one A4 page.
+    pub fn to_pdf(&self) -> Vec<u8> {
+        use pdf::{FONT_BOLD, FONT_MONO, FONT_REGULAR, MARGIN, PAGE_HEIGHT};
+
+        let mut content = String::new();
+        let mut y = PAGE_HEIGHT - MARGIN - 20.0;
+        // Writes one line at the cursor and moves it down by `advance`
+        let line = |content: &mut String, y: &mut f32, font: &str, size: f32, s: &str, advance: f32| {
+            content.push_str(&pdf::text(font, size, MARGIN, *y, s));
+            *y -= advance;
+        };
+
+        line(&mut content, &mut y, FONT_BOLD, 18.0, &truncate(&self.title), 20.0);
+        line(
+            &mut content,
+            &mut y,
+            FONT_REGULAR,
+            9.0,
+            &format!("OpenPGP key information sheet, generated {}", self.generated),
+            30.0,
+        );
+
+        line(&mut content, &mut y, FONT_BOLD, 12.0, "User IDs", 16.0);
+        for uid in capped(&self.user_ids, MAX_USER_IDS) {
+            line(&mut content, &mut y, FONT_REGULAR, 10.0, &uid, 14.0);
+        }
+
+        y -= 12.0;
+        line(&mut content, &mut y, FONT_BOLD, 12.0, "Fingerprint", 18.0);
+        line(&mut content, &mut y, FONT_MONO, 12.0, &self.fingerprint, 18.0);
+        for chunk in self.words.chunks(5) {
+            line(&mut content, &mut y, FONT_REGULAR, 10.0, &chunk.join(" "), 13.0);
+        }
+        y -= 3.0;
+        line(&mut content, &mut y, FONT_MONO, 8.0, &self.uri, 12.0);
+
+        y -= 12.0;
+        line(&mut content, &mut y, FONT_BOLD, 12.0, "Keys", 15.0);
+        for key in capped(&self.keys, MAX_KEYS) {
+            line(&mut content, &mut y, FONT_REGULAR, 9.0, &key, 13.0);
+        }
+
+        // QR code in the lower left corner, randomart to its right
+        let bottom = MARGIN + 30.0;
+        let width = self.qr.width();
+        let module = QR_SIZE / (width + 2 * QR_QUIET_ZONE) as f32;
+        let origin = MARGIN + QR_QUIET_ZONE as f32 * module;
+        let top = bottom + QR_SIZE - QR_QUIET_ZONE as f32 * module;
+        // One rectangle per run of dark modules in a row
+        for (row, modules) in self.qr.to_colors().chunks(width).enumerate() {
+            let mut col = 0;
+            while col < width {
+                let run = modules[col..].iter().take_while(|c| **c == Color::Dark).count();
+                if run > 0 {
+                    content.push_str(&pdf::filled_rect(
+                        origin + col as f32 * module,
+                        top - (row + 1) as f32 * module,
+                        run as f32 * module,
+                        module,
+                    ));
+                }
+                col += run.max(1);
+            }
+        }
+        content.push_str(&pdf::text(FONT_REGULAR, 8.0, MARGIN, bottom - 4.0, qr_caption(self.qr_content)));
+
+        let art_x = MARGIN + QR_SIZE + 40.0;
+        let mut art_y = bottom + QR_SIZE - 40.0;
+        for art_line in self.randomart.lines() {
+            content.push_str(&pdf::text(FONT_MONO, 10.0, art_x, art_y, art_line));
+            art_y -= 12.0;
+        }
+
+        content.push_str(&pdf::text(FONT_REGULAR, 9.0, MARGIN, MARGIN, CHECK_NOTE));
+
+        let mut doc = pdf::PdfDocument::new();
+        doc.add_page(content);
+        doc.to_bytes()
+    }
+
+    /// Renders the sheet as a self-contained HTML page, sized for printing.
+    pub fn to_html(&self) -> String {
+        let list = |items: &[String], max: usize| -> String {
+            capped(items, max)
+                .iter()
+                .map(|s| format!("<li>{}</li>\n", escape_html(s)))
+                .collect()
+        };
+        let qr = self
+            .qr
+            .render::<svg::Color>()
+            .quiet_zone(true)
+            .min_dimensions(260, 260)
+            .dark_color(svg::Color("#000000"))
+            .light_color(svg::Color("#ffffff"))
+            .build();
+        let words: String = self
+            .words
+            .chunks(5)
+            .map(|chunk| format!("{}<br>\n", escape_html(&chunk.join(" "))))
+            .collect();
+
+        format!(
+            "<!DOCTYPE html>\n\
+             <html><head><meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n\
+             <style>\n\
+             @page {{ size: A4; margin: 18mm; }}\n\
+             body {{ font-family: Helvetica, Arial, sans-serif; font-size: 10pt; }}\n\
+             h1 {{ font-size: 18pt; margin-bottom: 2pt; }}\n\
+             h2 {{ font-size: 12pt; margin: 14pt 0 4pt; }}\n\
+             ul {{ margin: 0; padding-left: 0; list-style: none; }}\n\
+             .mono {{ font-family: Courier, monospace; }}\n\
+             .fingerprint {{ font-size: 13pt; }}\n\
+             .bottom {{ display: flex; gap: 30pt; align-items: flex-start; margin-top: 16pt; }}\n\
+             .caption, .generated {{ font-size: 8pt; color: #444; }}\n\
+             </style></head><body>\n\
+             <h1>{title}</h1>\n\
+             <div class=\"generated\">OpenPGP key information sheet, generated {generated}</div>\n\
+             <h2>User IDs</h2>\n<ul>\n{user_ids}</ul>\n\
+             <h2>Fingerprint</h2>\n\
+             <div class=\"mono fingerprint\">{fingerprint}</div>\n\
+             <p>{words}</p>\n\
+             <div class=\"mono caption\">{uri}</div>\n\
+             <h2>Keys</h2>\n<ul>\n{keys}</ul>\n\
+             <div class=\"bottom\">\n\
+             <div>{qr}\n<div class=\"caption\">{caption}</div></div>\n\
+             <pre>{randomart}</pre>\n\
+             </div>\n\
+             <p>{note}</p>\n\
+             </body></html>\n",
+            title = escape_html(&self.title),
+            generated = self.generated,
+            user_ids = list(&self.user_ids, MAX_USER_IDS),
+            fingerprint = self.fingerprint,
+            words = words,
+            uri = self.uri,
+            keys = list(&self.keys, MAX_KEYS),
+            qr = qr,
+            caption = qr_caption(self.qr_content),
+            randomart = escape_html(&self.randomart),
+            note = CHECK_NOTE,
+        )
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
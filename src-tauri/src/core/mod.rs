@@ -1,6 +1,21 @@
 // Core business logic modules
 
+pub mod audit;
+pub mod autocrypt;
 pub mod config;
 pub mod crypto;
+pub mod expiry;
+pub mod fingerprint;
+pub mod gnupg;
+pub mod inspect;
+pub mod keyfile;
 pub mod keyring;
+pub mod keyserver;
+pub mod keysheet;
+pub mod paper;
+pub mod pdf;
+pub mod search;
+pub mod shamir;
 pub mod storage;
+pub mod trust;
+pub mod wkd;
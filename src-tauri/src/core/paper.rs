@@ -0,0 +1,262 @@
+// Paper backups of secret keys
+//
+// Only the secret part of each key packet is written out; everything else can
+// be rebuilt from the public cert. The secret material is copied as stored,
+// so a passphrase protected key stays protected on paper.
+//
+// Raw layout: a version byte, then for every secret key its packet version,
+// its 20 byte fingerprint, a big-endian u16 length and the secret key
+// material.
+use anyhow::{anyhow, Result};
+use sequoia_openpgp as openpgp;
+use openpgp::parse::Parse;
+use openpgp::cert::prelude::*;
+use openpgp::serialize::MarshalInto;
+use openpgp::{Cert, Packet};
+
+const FORMAT_VERSION: u8 = 0;
+const BYTES_PER_LINE: usize = 22;
+
+/// Extracts the secret key material of every secret key in `cert`.
+pub fn extract_secrets(cert: &Cert) -> Result<Vec<u8>> {
+    if !cert.is_tsk() {
+        return Err(anyhow!("Key {} has no secret key material", cert.fingerprint()));
+    }
+
+    let mut data = vec![FORMAT_VERSION];
+    for ka in cert.keys().secret() {
+        let key = ka.key();
+        let full = key.to_vec()?;
+        let public = key.parts_as_public().to_vec()?;
+        let secret = full
+            .strip_prefix(public.as_slice())
+            .ok_or_else(|| anyhow!("Unexpected key packet layout for {}", key.fingerprint()))?;
+
+        data.push(key.version());
+        data.extend_from_slice(key.fingerprint().as_bytes());
+        let len = u16::try_from(secret.len())
+            .map_err(|_| anyhow!("Secret key {} is too large for a paper backup", key.fingerprint()))?;
+        data.extend_from_slice(&len.to_be_bytes());
+        data.extend_from_slice(secret);
+    }
+    Ok(data)
+}
+
+/// Rebuilds the secret keys described by `data` on top of the public `cert`.
+pub fn restore_secrets(cert: &Cert, data: &[u8]) -> Result<Cert> {
+    let (&version, mut rest) = data.split_first().ok_or_else(|| anyhow!("Paper backup is empty"))?;
+    if version != FORMAT_VERSION {
+        return Err(anyhow!("Unsupported paper backup version {}", version));
+    }
+
+    let mut packets = Vec::new();
+    while !rest.is_empty() {
+        if rest.len() < 23 {
+            return Err(anyhow!("Paper backup is truncated"));
+        }
+        let key_version = rest[0];
+        let fingerprint = openpgp::Fingerprint::from_bytes(&rest[1..21]);
+        let len = u16::from_be_bytes([rest[21], rest[22]]) as usize;
+        let secret = rest
+            .get(23..23 + len)
+            .ok_or_else(|| anyhow!("Paper backup is truncated"))?;
+        rest = &rest[23 + len..];
+
+        if key_version != 4 {
+            return Err(anyhow!("Unsupported key version {}", key_version));
+        }
+
+        let ka = cert
+            .keys()
+            .find(|ka| ka.key().fingerprint() == fingerprint)
+            .ok_or_else(|| anyhow!("Key {} from the backup is not part of the public key", fingerprint))?;
+
+        // Secret key packets are the public key body followed by the secret part.
+        let mut body = ka.key().parts_as_public().to_vec()?;
+        body.extend_from_slice(secret);
+        let tag = if ka.primary() { 5 } else { 7 };
+        let packet = Packet::from_bytes(&with_header(tag, &body))?;
+
+        match &packet {
+            Packet::SecretKey(k) if k.fingerprint() == fingerprint => {}
+            Packet::SecretSubkey(k) if k.fingerprint() == fingerprint => {}
+            _ => return Err(anyhow!("Secret key {} could not be rebuilt", fingerprint)),
+        }
+        packets.push(packet);
+    }
+
+    if packets.is_empty() {
+        return Err(anyhow!("Paper backup holds no keys"));
+    }
+    cert.clone().insert_packets(packets)
+}
+
+// New format packet header.
+fn with_header(tag: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0xC0 | tag];
+    let len = body.len();
+    if len < 192 {
+        packet.push(len as u8);
+    } else if len < 8384 {
+        let l = len - 192;
+        packet.push(((l >> 8) + 192) as u8);
+        packet.push((l & 0xFF) as u8);
+    } else {
+        packet.push(0xFF);
+        packet.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// Renders the sheet for `cert`: a commented header followed by `data` as
+/// numbered hex lines, each ending with the CRC-24 of its bytes, and a line
+/// holding the CRC-24 of all data.
+pub fn to_text(cert: &Cert, data: &[u8]) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("# Secret portions of key {}\n", cert.fingerprint().to_hex()));
+    if let Some(uid) = cert.userids().next() {
+        out.push_str(&format!("# User ID: {}\n", String::from_utf8_lossy(uid.userid().value())));
+    }
+    let created: chrono::DateTime<chrono::Utc> = cert.primary_key().creation_time().into();
+    out.push_str(&format!("# Created: {}\n", created.format("%Y-%m-%d")));
+    for ka in cert.keys().secret() {
+        let which = if ka.primary() { "Primary" } else { "Subkey" };
+        out.push_str(&format!("# {}: {}\n", which, ka.key().fingerprint()));
+    }
+    out.push_str("#\n");
+    out.push_str("# To restore, import this sheet together with the public key.\n");
+    out.push_str("# Each line ends with a CRC-24 of its bytes, the last line holds\n");
+    out.push_str("# the CRC-24 of all data.\n");
+    out.push_str(&to_lines(data));
+    out
+}
+
+/// Encodes `data` as numbered hex lines with checksums. Lines starting with
+/// '#' may be put around them freely, `from_text` skips them.
+pub fn to_lines(data: &[u8]) -> String {
+    let mut out = String::new();
+    let mut line_no = 0;
+    for chunk in data.chunks(BYTES_PER_LINE) {
+        line_no += 1;
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02X}", b)).collect();
+        out.push_str(&format!("{:3}: {} {:06X}\n", line_no, hex.join(" "), crc24(chunk)));
+    }
+    out.push_str(&format!("{:3}: {:06X}\n", line_no + 1, crc24(data)));
+    out
+}
+
+/// Parses the lines produced by `to_lines`, checking every line's checksum.
+pub fn from_text(text: &str) -> Result<Vec<u8>> {
+    let mut lines = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (number, rest) = line
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Malformed line: {}", line))?;
+        let number: usize = number.trim().parse().map_err(|_| anyhow!("Malformed line number: {}", line))?;
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        lines.push((number, fields));
+    }
+
+    let ((total_no, total), data_lines) = lines
+        .split_last()
+        .ok_or_else(|| anyhow!("No paper backup data found"))?;
+
+    let mut data = Vec::new();
+    for (expected, (number, fields)) in data_lines.iter().enumerate() {
+        if *number != expected + 1 {
+            return Err(anyhow!("Line {} is missing", expected + 1));
+        }
+        let (crc, bytes) = fields
+            .split_last()
+            .ok_or_else(|| anyhow!("Line {} is empty", number))?;
+        let bytes = bytes
+            .iter()
+            .map(|b| u8::from_str_radix(b, 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| anyhow!("Line {} contains invalid hex", number))?;
+        if u32::from_str_radix(crc, 16).ok() != Some(crc24(&bytes)) {
+            return Err(anyhow!("Checksum mismatch on line {}", number));
+        }
+        data.extend_from_slice(&bytes);
+    }
+
+    if *total_no != data_lines.len() + 1 || total.len() != 1 {
+        return Err(anyhow!("Final checksum line is missing"));
+    }
+    if u32::from_str_radix(total[0], 16).ok() != Some(crc24(&data)) {
+        return Err(anyhow!("Checksum mismatch for the whole backup"));
+    }
+    Ok(data)
+}
+
+// CRC-24 as used by OpenPGP armor (RFC 4880, section 6.1).
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xB704CE;
+    for &byte in data {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= 0x1864CFB;
+            }
+        }
+    }
+    crc & 0xFFFFFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openpgp::crypto::Password;
+
+    fn tsk(password: Option<&str>) -> Cert {
+        CertBuilder::general_purpose(None, Some("Alice <alice@example.org>"))
+            .set_password(password.map(Password::from))
+            .generate()
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn round_trip() {
+        for password in [None, Some("correct horse")] {
+            let cert = tsk(password);
+            let text = to_text(&cert, &extract_secrets(&cert).unwrap());
+
+            let public = cert.clone().strip_secret_key_material();
+            let restored = restore_secrets(&public, &from_text(&text).unwrap()).unwrap();
+
+            assert_eq!(restored, cert);
+            assert_eq!(restored.keys().secret().count(), cert.keys().secret().count());
+            assert_eq!(restored.as_tsk().to_vec().unwrap(), cert.as_tsk().to_vec().unwrap());
+        }
+    }
+
+    #[test]
+    fn flipped_digit() {
+        let cert = tsk(None);
+        let text = to_text(&cert, &extract_secrets(&cert).unwrap());
+
+        // Flip the first hex digit of the second data line.
+        let mut lines: Vec<String> = text.lines().map(String::from).collect();
+        let line = lines.iter_mut().filter(|l| !l.starts_with('#')).nth(1).unwrap();
+        let at = line.find(": ").unwrap() + 2;
+        let flipped = if &line[at..at + 1] == "0" { "1" } else { "0" };
+        line.replace_range(at..at + 1, flipped);
+
+        let err = from_text(&lines.join("\n")).unwrap_err();
+        assert_eq!(err.to_string(), "Checksum mismatch on line 2");
+    }
+
+    #[test]
+    fn wrong_cert() {
+        let cert = tsk(None);
+        let data = extract_secrets(&cert).unwrap();
+        let other = tsk(None).strip_secret_key_material();
+        assert!(restore_secrets(&other, &data).is_err());
+    }
+}
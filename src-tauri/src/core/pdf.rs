@@ -0,0 +1,127 @@
+// Minimal PDF writer for printable sheets
+//
+// Only what the printable exports need: A4 pages, the built-in Courier and
+// Helvetica fonts and content streams written by the caller.
+
+pub const PAGE_WIDTH: f32 = 595.0; // A4 in points
+pub const PAGE_HEIGHT: f32 = 842.0;
+pub const MARGIN: f32 = 50.0;
+
+/// Font resource names available in every page's content stream: F1 is
+/// Courier, F2 Helvetica and F3 Helvetica-Bold.
+pub const FONT_MONO: &str = "F1";
+pub const FONT_REGULAR: &str = "F2";
+pub const FONT_BOLD: &str = "F3";
+
+#[derive(Default)]
+pub struct PdfDocument {
+    pages: Vec<String>, // Content streams
+}
+
+impl PdfDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_page(&mut self, content: String) {
+        self.pages.push(content);
+    }
+
+    /// Lays out monospaced lines over as many pages as needed.
+    pub fn text_pages(lines: &[String], font_size: f32) -> Self {
+        let leading = font_size * 1.2;
+        let per_page = ((PAGE_HEIGHT - 2.0 * MARGIN) / leading).floor().max(1.0) as usize;
+
+        let mut doc = Self::new();
+        for chunk in lines.chunks(per_page) {
+            let mut content = String::new();
+            for (i, line) in chunk.iter().enumerate() {
+                let y = PAGE_HEIGHT - MARGIN - font_size - i as f32 * leading;
+                content.push_str(&text(FONT_MONO, font_size, MARGIN, y, line));
+            }
+            doc.add_page(content);
+        }
+        if doc.pages.is_empty() {
+            doc.add_page(String::new());
+        }
+        doc
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Objects 1-2 are the catalog and page tree, 3-5 the fonts, then a
+        // page object and its content stream for every page.
+        let mut objects: Vec<String> = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            String::new(), // Page tree, filled in below
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+        ];
+
+        let mut kids = Vec::new();
+        for content in &self.pages {
+            let page_id = objects.len() + 1;
+            kids.push(format!("{} 0 R", page_id));
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                page_id + 1
+            ));
+            objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+        }
+        objects[1] = format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), kids.len());
+
+        let mut out = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+        }
+
+        let xref = out.len();
+        out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .as_bytes(),
+        );
+        out
+    }
+}
+
+/// Content stream operators drawing one line of text at (x, y).
+pub fn text(font: &str, size: f32, x: f32, y: f32, s: &str) -> String {
+    format!("BT /{} {} Tf {} {} Td ({}) Tj ET\n", font, size, x, y, escape(s))
+}
+
+/// Content stream operators filling a rectangle with its lower left corner
+/// at (x, y) in black.
+pub fn filled_rect(x: f32, y: f32, width: f32, height: f32) -> String {
+    format!("{} {} {} {} re f\n", x, y, width, height)
+}
+
+// Escapes a string literal; characters outside Latin-1 become '?'.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            c if (c as u32) < 0x100 && (c as u32) >= 0xA0 => out.push_str(&format!("\\{:03o}", c as u32)),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
@@ -0,0 +1,192 @@
+// Key search over the vault's SQLite tables
+use crate::core::storage::Vault;
+use crate::models::{ExpiryState, KeyMetadata, KeySearchQuery, KeySearchResult, KeySortField};
+use anyhow::Result;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use sequoia_openpgp as openpgp;
+use openpgp::parse::Parse;
+use openpgp::Cert;
+
+const DEFAULT_EXPIRING_WITHIN_DAYS: u32 = 30;
+
+/// Replaces the key ID index entries of `fingerprint` with the primary key
+/// and subkeys of `cert`.
+pub fn index_key_ids(conn: &Connection, fingerprint: &str, cert: &Cert) -> Result<()> {
+    conn.execute("DELETE FROM key_ids WHERE fingerprint = ?1", params![fingerprint])?;
+    for key in cert.keys() {
+        conn.execute(
+            "INSERT INTO key_ids (key_id, key_fingerprint, fingerprint) VALUES (?1, ?2, ?3)",
+            params![key.key().keyid().to_hex(), key.key().fingerprint().to_hex(), fingerprint],
+        )?;
+    }
+    Ok(())
+}
+
+/// Builds the key ID index if it is empty but the vault holds keys.
+pub fn ensure_key_index(vault: &Vault) -> Result<()> {
+    let conn = vault.conn.lock().unwrap();
+    let indexed: i64 = conn.query_row("SELECT COUNT(*) FROM key_ids", [], |row| row.get(0))?;
+    if indexed > 0 {
+        return Ok(());
+    }
+
+    let mut stmt = conn.prepare("SELECT fingerprint, key_content FROM keys WHERE key_content IS NOT NULL")?;
+    let rows: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    for (fingerprint, content) in rows {
+        let indexed = Cert::from_bytes(content.as_bytes())
+            .and_then(|cert| index_key_ids(&conn, &fingerprint, &cert));
+        if let Err(e) = indexed {
+            log::error!("Failed to index key {}: {}", fingerprint, e);
+        }
+    }
+    Ok(())
+}
+
+/// Finds the keys matching `query`, sorted and paginated in SQLite.
+pub fn search_keys(vault: &Vault, query: &KeySearchQuery) -> Result<KeySearchResult> {
+    let mut conditions = vec!["metadata_json IS NOT NULL".to_string()];
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(text) = non_empty(&query.text) {
+        let id = normalize_hex(text);
+        conditions.push(format!(
+            "(user_name LIKE ? ESCAPE '\\' OR user_email LIKE ? ESCAPE '\\' OR fingerprint LIKE ? ESCAPE '\\' OR {})",
+            KEY_ID_MATCH
+        ));
+        values.push(contains(text).into());
+        values.push(contains(text).into());
+        values.push(suffix(&id).into());
+        values.push(id.clone().into());
+        values.push(suffix(&id).into());
+    }
+    if let Some(name) = non_empty(&query.name) {
+        conditions.push("user_name LIKE ? ESCAPE '\\'".to_string());
+        values.push(contains(name).into());
+    }
+    if let Some(email) = non_empty(&query.email) {
+        conditions.push("user_email LIKE ? ESCAPE '\\'".to_string());
+        values.push(contains(email).into());
+    }
+    if let Some(fingerprint) = non_empty(&query.fingerprint) {
+        conditions.push("fingerprint LIKE ? ESCAPE '\\'".to_string());
+        values.push(suffix(&normalize_hex(fingerprint)).into());
+    }
+    if let Some(key_id) = non_empty(&query.key_id) {
+        let id = normalize_hex(key_id);
+        conditions.push(KEY_ID_MATCH.to_string());
+        values.push(id.clone().into());
+        values.push(suffix(&id).into());
+    }
+    if let Some(tag) = non_empty(&query.tag) {
+        conditions.push(
+            "EXISTS (SELECT 1 FROM json_each(keys.metadata_json, '$.tags') WHERE value = ? COLLATE NOCASE)"
+                .to_string(),
+        );
+        values.push(tag.trim().to_string().into());
+    }
+    if let Some(favorite) = query.favorite {
+        conditions.push("COALESCE(is_favorite, 0) = ?".to_string());
+        values.push(Value::Integer(favorite as i64));
+    }
+    if let Some(private) = query.private {
+        conditions.push("COALESCE(is_private, 0) = ?".to_string());
+        values.push(Value::Integer(private as i64));
+    }
+    if let Some(expiry) = query.expiry {
+        // expires_at is stored as RFC 3339 in UTC, which sorts lexically.
+        let now = chrono::Utc::now();
+        let days = query.expiring_within_days.unwrap_or(DEFAULT_EXPIRING_WITHIN_DAYS);
+        let soon = now + chrono::Duration::days(days as i64);
+        let now = now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+        let soon = soon.to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+
+        match expiry {
+            ExpiryState::Expired => {
+                conditions.push("(expires_at IS NOT NULL AND expires_at <= ?)".to_string());
+                values.push(now.into());
+            }
+            ExpiryState::Expiring => {
+                conditions.push("(expires_at IS NOT NULL AND expires_at > ? AND expires_at <= ?)".to_string());
+                values.push(now.into());
+                values.push(soon.into());
+            }
+            ExpiryState::Valid => {
+                conditions.push("(expires_at IS NULL OR expires_at > ?)".to_string());
+                values.push(now.into());
+            }
+            ExpiryState::NeverExpires => conditions.push("expires_at IS NULL".to_string()),
+        }
+    }
+
+    let where_clause = conditions.join(" AND ");
+    let conn = vault.conn.lock().unwrap();
+
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM keys WHERE {}", where_clause),
+        params_from_iter(values.iter()),
+        |row| row.get(0),
+    )?;
+
+    let direction = if query.descending { "DESC" } else { "ASC" };
+    let order = match query.sort_by {
+        KeySortField::Name => format!("user_name COLLATE NOCASE {}", direction),
+        KeySortField::Email => format!("user_email COLLATE NOCASE {}", direction),
+        KeySortField::CreatedAt => format!("created_at {}", direction),
+        // Keys that never expire go last in ascending order.
+        KeySortField::ExpiresAt => format!("expires_at IS NULL {0}, expires_at {0}", direction),
+        KeySortField::Fingerprint => format!("fingerprint {}", direction),
+    };
+
+    values.push(Value::Integer(query.limit.map(|l| l as i64).unwrap_or(-1)));
+    values.push(Value::Integer(query.offset as i64));
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT metadata_json FROM keys WHERE {} ORDER BY {}, fingerprint LIMIT ? OFFSET ?",
+        where_clause, order
+    ))?;
+    let rows = stmt.query_map(params_from_iter(values.iter()), |row| row.get::<_, String>(0))?;
+
+    let mut keys: Vec<KeyMetadata> = Vec::new();
+    for json in rows {
+        match serde_json::from_str(&json?) {
+            Ok(metadata) => keys.push(metadata),
+            Err(e) => log::error!("Skipping key with unreadable metadata: {}", e),
+        }
+    }
+
+    Ok(KeySearchResult {
+        keys,
+        total: total as usize,
+    })
+}
+
+// Matches a key ID exactly or a (sub)key fingerprint by suffix; takes two parameters.
+const KEY_ID_MATCH: &str = "EXISTS (SELECT 1 FROM key_ids WHERE key_ids.fingerprint = keys.fingerprint \
+     AND (key_ids.key_id = ? OR key_ids.key_fingerprint LIKE ? ESCAPE '\\'))";
+
+fn non_empty(s: &Option<String>) -> Option<&str> {
+    s.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+// Accepts "0xDEADBEEF", spaced fingerprints and lower case hex.
+fn normalize_hex(s: &str) -> String {
+    let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    let s = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(&s);
+    s.to_ascii_uppercase()
+}
+
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+fn contains(s: &str) -> String {
+    format!("%{}%", escape_like(s))
+}
+
+fn suffix(s: &str) -> String {
+    format!("%{}", escape_like(s))
+}
@@ -0,0 +1,291 @@
+// Shamir secret sharing of private keys
+//
+// Every byte of the secret is shared with its own random polynomial over
+// GF(2^8); share `i` holds the polynomials evaluated at x = i. Any
+// `threshold` shares give back the secret, fewer reveal nothing about it.
+//
+// Share layout: version, threshold, share index, the 20 byte fingerprint of
+// the key and a big-endian u32 length, followed by the share bytes.
+use anyhow::{anyhow, Result};
+use sequoia_openpgp as openpgp;
+use openpgp::armor::{Kind, Reader, ReaderMode, Writer};
+use openpgp::crypto::mem::Protected;
+use std::io::{Read, Write};
+
+const SHARE_VERSION: u8 = 1;
+const HEADER_LEN: usize = 1 + 1 + 1 + 20 + 4;
+
+/// One decoded share.
+pub struct KeyShare {
+    pub threshold: u8,
+    pub index: u8,
+    pub fingerprint: openpgp::Fingerprint,
+    pub data: Vec<u8>,
+}
+
+impl KeyShare {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![SHARE_VERSION, self.threshold, self.index];
+        out.extend_from_slice(self.fingerprint.as_bytes());
+        out.extend_from_slice(&(self.data.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < HEADER_LEN {
+            return Err(anyhow!("Key share is truncated"));
+        }
+        if bytes[0] != SHARE_VERSION {
+            return Err(anyhow!("Unsupported key share version {}", bytes[0]));
+        }
+        let len = u32::from_be_bytes([bytes[23], bytes[24], bytes[25], bytes[26]]) as usize;
+        let data = bytes
+            .get(HEADER_LEN..HEADER_LEN + len)
+            .ok_or_else(|| anyhow!("Key share is truncated"))?;
+
+        Ok(Self {
+            threshold: bytes[1],
+            index: bytes[2],
+            fingerprint: openpgp::Fingerprint::from_bytes(&bytes[3..23]),
+            data: data.to_vec(),
+        })
+    }
+
+    /// Encodes the share as numbered hex lines with checksums, printable like
+    /// a paper backup.
+    pub fn to_text(&self, shares: u8) -> String {
+        format!(
+            "# Key share {} of {} for key {}\n# Any {} shares restore the key.\n{}",
+            self.index,
+            shares,
+            self.fingerprint.to_hex(),
+            self.threshold,
+            crate::core::paper::to_lines(&self.to_bytes())
+        )
+    }
+
+    pub fn to_armor(&self, shares: u8) -> Result<String> {
+        let share = format!("{} of {}, any {} restore the key", self.index, shares, self.threshold);
+        let fingerprint = self.fingerprint.to_hex();
+        let headers = vec![("Comment", share.as_str()), ("Comment", fingerprint.as_str())];
+
+        let mut writer = Writer::with_headers(Vec::new(), Kind::File, headers)?;
+        writer.write_all(&self.to_bytes())?;
+        Ok(String::from_utf8(writer.finalize()?)?)
+    }
+
+    /// Decodes a share in either of the formats above.
+    pub fn parse(text: &str) -> Result<Self> {
+        if text.contains("-----BEGIN PGP") {
+            let mut reader = Reader::from_bytes(text.as_bytes(), ReaderMode::Tolerant(Some(Kind::File)));
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+            Self::from_bytes(&bytes)
+        } else {
+            Self::from_bytes(&crate::core::paper::from_text(text)?)
+        }
+    }
+}
+
+/// Splits `secret` into `shares` shares, any `threshold` of which recover it.
+pub fn split(
+    secret: &Protected,
+    fingerprint: &openpgp::Fingerprint,
+    threshold: u8,
+    shares: u8,
+) -> Result<Vec<KeyShare>> {
+    if threshold < 2 {
+        return Err(anyhow!("The threshold must be at least 2"));
+    }
+    if threshold > shares {
+        return Err(anyhow!("The threshold cannot exceed the number of shares"));
+    }
+
+    let mut out: Vec<KeyShare> = (1..=shares)
+        .map(|index| KeyShare {
+            threshold,
+            index,
+            fingerprint: fingerprint.clone(),
+            data: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+
+    // coefficients[0] is the secret byte, the rest is random.
+    let mut coefficients = Protected::new(threshold as usize);
+    for &byte in secret.iter() {
+        coefficients[0] = byte;
+        openpgp::crypto::random(&mut coefficients[1..]);
+        for share in out.iter_mut() {
+            share.data.push(evaluate(&coefficients, share.index));
+        }
+    }
+
+    Ok(out)
+}
+
+/// Recovers the secret from at least `threshold` shares of the same key.
+pub fn combine(shares: &[KeyShare]) -> Result<Protected> {
+    let first = shares.first().ok_or_else(|| anyhow!("No key shares given"))?;
+
+    for share in shares {
+        if share.fingerprint != first.fingerprint {
+            return Err(anyhow!("The shares belong to different keys"));
+        }
+        if share.threshold != first.threshold || share.data.len() != first.data.len() {
+            return Err(anyhow!("The shares do not belong to the same split"));
+        }
+        if share.index == 0 {
+            return Err(anyhow!("Invalid share index 0"));
+        }
+    }
+
+    let mut indices: Vec<u8> = shares.iter().map(|s| s.index).collect();
+    indices.sort_unstable();
+    indices.dedup();
+    if indices.len() != shares.len() {
+        return Err(anyhow!("The same share was given more than once"));
+    }
+    if shares.len() < first.threshold as usize {
+        return Err(anyhow!(
+            "{} shares are needed to restore the key, only {} given",
+            first.threshold,
+            shares.len()
+        ));
+    }
+
+    // Lagrange interpolation at x = 0; subtraction is XOR in GF(2^8).
+    let used = &shares[..first.threshold as usize];
+    let weights: Vec<u8> = used
+        .iter()
+        .map(|share| {
+            used.iter()
+                .filter(|other| other.index != share.index)
+                .fold(1, |acc, other| {
+                    mul(acc, mul(other.index, inverse(other.index ^ share.index)))
+                })
+        })
+        .collect();
+
+    let mut secret = Protected::new(first.data.len());
+    for (i, byte) in secret.iter_mut().enumerate() {
+        *byte = used
+            .iter()
+            .zip(&weights)
+            .fold(0, |acc, (share, &weight)| acc ^ mul(share.data[i], weight));
+    }
+    Ok(secret)
+}
+
+// Horner's scheme in GF(2^8).
+fn evaluate(coefficients: &[u8], x: u8) -> u8 {
+    coefficients.iter().rev().fold(0, |acc, &c| mul(acc, x) ^ c)
+}
+
+// Multiplication modulo the AES polynomial x^8 + x^4 + x^3 + x + 1. Always
+// runs eight rounds and selects with masks instead of branching, so the time
+// taken doesn't depend on the (secret) operands.
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (0x1B & carry);
+        b >>= 1;
+    }
+    product
+}
+
+// a^254 is the multiplicative inverse of a in GF(2^8); a fixed number of
+// constant time multiplications.
+fn inverse(a: u8) -> u8 {
+    let mut result = 1;
+    for _ in 0..254 {
+        result = mul(result, a);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(byte: u8) -> openpgp::Fingerprint {
+        openpgp::Fingerprint::from_bytes(&[byte; 20])
+    }
+
+    fn copy(share: &KeyShare) -> KeyShare {
+        KeyShare::from_bytes(&share.to_bytes()).unwrap()
+    }
+
+    #[test]
+    fn field_arithmetic() {
+        // FIPS 197, section 4.2.
+        assert_eq!(mul(0x57, 0x83), 0xC1);
+        assert_eq!(mul(0x57, 0x13), 0xFE);
+        for a in 1..=255u8 {
+            assert_eq!(mul(a, inverse(a)), 1, "inverse of {:#04x}", a);
+            assert_eq!(mul(a, 0), 0);
+            assert_eq!(mul(a, 1), a);
+        }
+    }
+
+    #[test]
+    fn every_subset() {
+        let secret = Protected::from(&b"the company signing key"[..]);
+        let shares = split(&secret, &fingerprint(1), 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for subset in 1u32..(1 << shares.len()) {
+            let picked: Vec<KeyShare> = shares
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| subset & (1 << i) != 0)
+                .map(|(_, share)| copy(share))
+                .collect();
+            let combined = combine(&picked);
+            if picked.len() >= 3 {
+                assert_eq!(combined.unwrap(), secret, "subset {:05b}", subset);
+            } else {
+                assert!(combined.is_err(), "subset {:05b}", subset);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_combinations() {
+        let secret = Protected::from(&b"secret"[..]);
+        let shares = split(&secret, &fingerprint(1), 2, 3).unwrap();
+
+        assert!(combine(&[]).is_err());
+        assert!(combine(&[copy(&shares[0])]).is_err());
+        assert!(combine(&[copy(&shares[0]), copy(&shares[0])]).is_err());
+
+        let other_key = split(&secret, &fingerprint(2), 2, 3).unwrap();
+        assert!(combine(&[copy(&shares[0]), copy(&other_key[1])]).is_err());
+
+        let other_split = split(&secret, &fingerprint(1), 3, 3).unwrap();
+        assert!(combine(&[copy(&shares[0]), copy(&other_split[1]), copy(&other_split[2])]).is_err());
+
+        assert!(split(&secret, &fingerprint(1), 1, 3).is_err());
+        assert!(split(&secret, &fingerprint(1), 4, 3).is_err());
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let secret = Protected::from(&[0u8, 1, 2, 0xFF, 0x80][..]);
+        let shares = split(&secret, &fingerprint(7), 2, 4).unwrap();
+
+        let parsed: Vec<KeyShare> = shares
+            .iter()
+            .map(|share| KeyShare::parse(&share.to_text(4)).unwrap())
+            .collect();
+        for (share, parsed) in shares.iter().zip(&parsed) {
+            assert_eq!(parsed.threshold, share.threshold);
+            assert_eq!(parsed.index, share.index);
+            assert_eq!(parsed.fingerprint, share.fingerprint);
+            assert_eq!(parsed.data, share.data);
+        }
+        assert_eq!(combine(&parsed[2..]).unwrap(), secret);
+    }
+}
@@ -7,6 +7,7 @@ use std::sync::Mutex;
 pub struct Vault {
     pub db_path: PathBuf,
     pub conn: Mutex<Connection>,
+    pub validity: Mutex<super::trust::ValidityCache>,
 }
 
 impl Vault {
@@ -55,9 +56,40 @@ impl Vault {
             [],
         )?;
 
-        Ok(Self {
+        // Owner trust lives in its own table so it survives deleting and
+        // re-importing a key.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS owner_trust (
+                fingerprint TEXT PRIMARY KEY,
+                trust TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Key IDs and fingerprints of every primary key and subkey, so keys
+        // can be looked up by subkey without parsing every cert.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS key_ids (
+                key_id TEXT NOT NULL,
+                key_fingerprint TEXT NOT NULL,
+                fingerprint TEXT NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS key_ids_key_id ON key_ids (key_id)",
+            [],
+        )?;
+
+        let vault = Self {
             db_path,
             conn: Mutex::new(conn),
-        })
+            validity: Mutex::default(),
+        };
+
+        // Vaults created before the key ID index existed are indexed once.
+        super::search::ensure_key_index(&vault)?;
+
+        Ok(vault)
     }
 }
@@ -0,0 +1,268 @@
+// Owner trust storage and Web-of-Trust validity computation
+
+use crate::core::storage::Vault;
+use crate::models::{KeyMetadata, OwnerTrust, UserIdValidity, Validity};
+use anyhow::Result;
+use rusqlite::params;
+use sequoia_openpgp as openpgp;
+use openpgp::parse::Parse;
+use openpgp::policy::StandardPolicy;
+use openpgp::Cert;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Number of marginally trusted introducers needed to make a user ID fully
+/// valid (GnuPG's `marginals-needed`).
+const MARGINALS_NEEDED: usize = 3;
+
+/// Maximum length of a certification path from a trust root.
+const MAX_CERT_DEPTH: usize = 5;
+
+pub fn set_owner_trust(vault: &Vault, fingerprint: &str, trust: OwnerTrust) -> Result<()> {
+    let conn = vault.conn.lock().unwrap();
+    conn.execute(
+        "INSERT INTO owner_trust (fingerprint, trust) VALUES (?1, ?2)
+        ON CONFLICT(fingerprint) DO UPDATE SET trust=excluded.trust",
+        params![fingerprint, trust.to_string()],
+    )?;
+    drop(conn);
+    invalidate_validity(vault);
+    Ok(())
+}
+
+pub fn load_owner_trust(vault: &Vault) -> Result<HashMap<String, OwnerTrust>> {
+    let conn = vault.conn.lock().unwrap();
+    let mut stmt = conn.prepare("SELECT fingerprint, trust FROM owner_trust")?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    let mut trust = HashMap::new();
+    for row in rows {
+        let (fingerprint, level) = row?;
+        if let Ok(level) = level.parse() {
+            trust.insert(fingerprint, level);
+        }
+    }
+    Ok(trust)
+}
+
+/// Loads every parseable cert stored in the vault.
+pub fn load_vault_certs(vault: &Vault) -> Result<Vec<Cert>> {
+    Ok(crate::core::keyring::load_all_key_contents(vault)?
+        .into_iter()
+        .filter_map(|(_, content)| Cert::from_bytes(content.as_bytes()).ok())
+        .collect())
+}
+
+/// A verified, live certification on one of a cert's user IDs.
+struct Certification {
+    issuer: String,
+    trust_signature: Option<(u8, u8)>,
+}
+
+/// Computes the validity of every user ID of every cert.
+///
+/// This follows the classic PGP trust model: keys with ultimate owner trust
+/// are the trust roots, a user ID is fully valid when it is certified by one
+/// fully trusted or `MARGINALS_NEEDED` marginally trusted introducers, and a
+/// key can only act as an introducer once it is fully valid itself. Trust
+/// signatures issued by a trust root grant owner trust to the target without
+/// an explicit owner trust entry.
+pub fn compute_validity(
+    certs: &[Cert],
+    owner_trust: &HashMap<String, OwnerTrust>,
+) -> HashMap<String, Vec<UserIdValidity>> {
+    let p = StandardPolicy::new();
+
+    let by_fingerprint: HashMap<String, &Cert> =
+        certs.iter().map(|c| (c.fingerprint().to_hex(), c)).collect();
+
+    // Resolve issuer key handles (fingerprint or key ID) to vault certs.
+    let issuer_index: HashMap<openpgp::KeyID, String> = certs
+        .iter()
+        .map(|c| (c.keyid(), c.fingerprint().to_hex()))
+        .collect();
+
+    // Verify all third-party certifications once up front.
+    let mut certifications: HashMap<String, Vec<(String, Vec<Certification>)>> = HashMap::new();
+    for cert in certs {
+        let vc = match cert.with_policy(&p, None) {
+            Ok(vc) if vc.alive().is_ok() && !matches!(vc.revocation_status(), openpgp::types::RevocationStatus::Revoked(_)) => vc,
+            _ => continue,
+        };
+
+        let mut userids = Vec::new();
+        for ua in vc.userids() {
+            let mut verified = Vec::new();
+            for sig in ua.certifications() {
+                if sig.signature_alive(None, None).is_err() {
+                    continue;
+                }
+                let issuer = sig
+                    .get_issuers()
+                    .iter()
+                    .find_map(|handle| issuer_index.get(&openpgp::KeyID::from(handle)).cloned());
+                let issuer = match issuer {
+                    Some(fp) if fp != cert.fingerprint().to_hex() => fp,
+                    _ => continue,
+                };
+                let issuer_cert = by_fingerprint[&issuer];
+                if sig
+                    .verify_userid_binding(issuer_cert.primary_key().key(), cert.primary_key().key(), ua.userid())
+                    .is_ok()
+                {
+                    verified.push(Certification {
+                        issuer,
+                        trust_signature: sig.trust_signature(),
+                    });
+                }
+            }
+            userids.push((String::from_utf8_lossy(ua.userid().value()).to_string(), verified));
+        }
+        certifications.insert(cert.fingerprint().to_hex(), userids);
+    }
+
+    // Owner trust as set by the user, raised by trust signatures from roots.
+    let mut effective_trust = owner_trust.clone();
+    for (fingerprint, userids) in &certifications {
+        if owner_trust.get(fingerprint) == Some(&OwnerTrust::Never) {
+            continue;
+        }
+        for certification in userids.iter().flat_map(|(_, c)| c) {
+            let from_root = owner_trust.get(&certification.issuer) == Some(&OwnerTrust::Ultimate);
+            let granted = match certification.trust_signature {
+                Some((depth, amount)) if from_root && depth > 0 && amount >= 120 => OwnerTrust::Full,
+                Some((depth, amount)) if from_root && depth > 0 && amount >= 60 => OwnerTrust::Marginal,
+                _ => continue,
+            };
+            let current = effective_trust.entry(fingerprint.clone()).or_default();
+            if granted > *current {
+                *current = granted;
+            }
+        }
+    }
+
+    let userid_validity = |certs: &[Certification], key_validity: &HashMap<String, Validity>| {
+        let mut full = 0;
+        let mut marginal = 0;
+        for certification in certs {
+            if key_validity.get(&certification.issuer).copied().unwrap_or_default() < Validity::Full {
+                continue;
+            }
+            match effective_trust.get(&certification.issuer).copied().unwrap_or_default() {
+                OwnerTrust::Ultimate | OwnerTrust::Full => full += 1,
+                OwnerTrust::Marginal => marginal += 1,
+                _ => {}
+            }
+        }
+        if full > 0 || marginal >= MARGINALS_NEEDED {
+            Validity::Full
+        } else if marginal > 0 {
+            Validity::Marginal
+        } else {
+            Validity::Unknown
+        }
+    };
+
+    // Key level validity: the best validity of any of the key's user IDs.
+    let mut key_validity: HashMap<String, Validity> = HashMap::new();
+    for fingerprint in certifications.keys() {
+        if owner_trust.get(fingerprint) == Some(&OwnerTrust::Ultimate) {
+            key_validity.insert(fingerprint.clone(), Validity::Ultimate);
+        }
+    }
+
+    // Every round extends the certification paths by one hop, so each round
+    // reads only the validity of the previous one.
+    for _ in 0..MAX_CERT_DEPTH {
+        let previous = key_validity.clone();
+        let mut changed = false;
+        for (fingerprint, userids) in &certifications {
+            let current = previous.get(fingerprint).copied().unwrap_or_default();
+            if current >= Validity::Full {
+                continue;
+            }
+            let best = userids
+                .iter()
+                .map(|(_, c)| userid_validity(c, &previous))
+                .max()
+                .unwrap_or_default();
+            if best > current {
+                key_validity.insert(fingerprint.clone(), best);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    certifications
+        .iter()
+        .map(|(fingerprint, userids)| {
+            let is_root = key_validity.get(fingerprint) == Some(&Validity::Ultimate);
+            let validity = userids
+                .iter()
+                .map(|(user_id, c)| UserIdValidity {
+                    user_id: user_id.clone(),
+                    validity: if is_root {
+                        Validity::Ultimate
+                    } else {
+                        userid_validity(c, &key_validity)
+                    },
+                })
+                .collect();
+            (fingerprint.clone(), validity)
+        })
+        .collect()
+}
+
+/// Computed validity of every stored key, kept on the vault until a cert or
+/// an owner trust value changes.
+#[derive(Default)]
+pub struct ValidityCache {
+    generation: u64, // Bumped on every write, so a racing computation is not kept
+    validity: Option<Arc<HashMap<String, Vec<UserIdValidity>>>>,
+}
+
+/// Drops the cached validity; called after every cert or owner trust write.
+pub fn invalidate_validity(vault: &Vault) {
+    let mut cache = vault.validity.lock().unwrap();
+    cache.generation += 1;
+    cache.validity = None;
+}
+
+/// Returns the validity of every stored key, computing it if needed.
+pub fn vault_validity(vault: &Vault) -> Result<Arc<HashMap<String, Vec<UserIdValidity>>>> {
+    let generation = {
+        let cache = vault.validity.lock().unwrap();
+        if let Some(validity) = &cache.validity {
+            return Ok(validity.clone());
+        }
+        cache.generation
+    };
+
+    let owner_trust = load_owner_trust(vault)?;
+    let certs = load_vault_certs(vault)?;
+    let validity = Arc::new(compute_validity(&certs, &owner_trust));
+
+    let mut cache = vault.validity.lock().unwrap();
+    if cache.generation == generation {
+        cache.validity = Some(validity.clone());
+    }
+    Ok(validity)
+}
+
+/// Fills in owner trust and computed user ID validity for listed keys.
+pub fn annotate_keys(vault: &Vault, keys: &mut [KeyMetadata]) -> Result<()> {
+    let owner_trust = load_owner_trust(vault)?;
+    let validity = vault_validity(vault)?;
+
+    for key in keys.iter_mut() {
+        key.owner_trust = owner_trust.get(&key.fingerprint).copied().unwrap_or_default();
+        key.validity = validity.get(&key.fingerprint).cloned().unwrap_or_default();
+    }
+    Ok(())
+}
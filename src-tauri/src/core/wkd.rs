@@ -0,0 +1,139 @@
+// Offline Web Key Directory generation (draft-koch-openpgp-webkey-service)
+use crate::models::{WkdEntry, WkdLayout, WkdReport};
+use anyhow::{anyhow, Result};
+use sequoia_openpgp as openpgp;
+use openpgp::policy::StandardPolicy;
+use openpgp::types::HashAlgorithm;
+use openpgp::Cert;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const ZBASE32_ALPHABET: &[u8; 32] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
+
+/// The WKD file name of a mail address's local part: the z-base-32 encoded
+/// SHA-1 of the local part with ASCII letters lower-cased.
+pub fn hashed_local_part(local_part: &str) -> Result<String> {
+    let mut hasher = HashAlgorithm::SHA1.context()?;
+    hasher.update(local_part.to_ascii_lowercase().as_bytes());
+    let mut digest = vec![0u8; hasher.digest_size()];
+    hasher.digest(&mut digest)?;
+    Ok(zbase32(&digest))
+}
+
+fn zbase32(data: &[u8]) -> String {
+    let mut out = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ZBASE32_ALPHABET[((buffer >> bits) & 0x1F) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ZBASE32_ALPHABET[((buffer << (5 - bits)) & 0x1F) as usize] as char);
+    }
+    out
+}
+
+/// Whether `domain` is a plain host name: dot separated labels of ASCII
+/// letters, digits and hyphens. Anything else could escape the output dir.
+fn is_valid_domain(domain: &str) -> bool {
+    domain.len() <= 253
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        })
+}
+
+/// Directory holding `hu/` and `policy` for `layout`, relative to the web root.
+fn layout_dir(layout: WkdLayout, domain: &str) -> PathBuf {
+    let base = Path::new(".well-known").join("openpgpkey");
+    match layout {
+        WkdLayout::Direct => base,
+        WkdLayout::Advanced => base.join(domain),
+    }
+}
+
+/// Writes the WKD tree for the user IDs of `certs` in `domain` below
+/// `output_dir`, once per layout. Certs are published with their public
+/// parts only.
+pub fn generate(certs: &[Cert], output_dir: &Path, domain: &str, layouts: &[WkdLayout]) -> Result<WkdReport> {
+    let p = StandardPolicy::new();
+    let domain = domain.trim().trim_start_matches('@').to_lowercase();
+    if domain.is_empty() {
+        return Err(anyhow!("A domain is required"));
+    }
+    if !is_valid_domain(&domain) {
+        return Err(anyhow!("Invalid domain: {}", domain));
+    }
+
+    // 1. Group certs by mail address; only valid user IDs are published.
+    let mut by_address: BTreeMap<String, Vec<&Cert>> = BTreeMap::new();
+    let mut skipped = Vec::new();
+    for cert in certs {
+        let mut published = false;
+        if let Ok(vc) = cert.with_policy(&p, None) {
+            for ua in vc.userids() {
+                let address = match ua.userid().email2() {
+                    Ok(Some(address)) => address.to_string(),
+                    _ => continue,
+                };
+                let matches = address
+                    .rsplit_once('@')
+                    .map(|(_, d)| d.eq_ignore_ascii_case(&domain))
+                    .unwrap_or(false);
+                if matches {
+                    let entry = by_address.entry(address.to_lowercase()).or_default();
+                    if !entry.iter().any(|c| c.fingerprint() == cert.fingerprint()) {
+                        entry.push(cert);
+                    }
+                    published = true;
+                }
+            }
+        }
+        if !published {
+            skipped.push(cert.fingerprint().to_hex());
+        }
+    }
+
+    if by_address.is_empty() {
+        return Err(anyhow!("No valid user ID has an address in {}", domain));
+    }
+
+    // 2. Write one file per address and the (empty) policy file per layout.
+    let mut entries = Vec::new();
+    for &layout in layouts {
+        let dir = layout_dir(layout, &domain);
+        std::fs::create_dir_all(output_dir.join(&dir).join("hu"))?;
+        std::fs::write(output_dir.join(&dir).join("policy"), b"")?;
+
+        for (address, certs) in &by_address {
+            let local_part = address.rsplit_once('@').map(|(l, _)| l).unwrap_or(address);
+            let path = dir.join("hu").join(hashed_local_part(local_part)?);
+
+            let certs: Vec<Cert> = certs.iter().map(|c| (*c).clone()).collect();
+            let bytes = crate::core::crypto::serialize_public_keyring(&certs, false)?;
+            std::fs::write(output_dir.join(&path), bytes)?;
+
+            entries.push(WkdEntry {
+                address: address.clone(),
+                fingerprints: certs.iter().map(|c| c.fingerprint().to_hex()).collect(),
+                path: path.to_string_lossy().replace('\\', "/"),
+            });
+        }
+    }
+
+    Ok(WkdReport {
+        output_dir: output_dir.to_string_lossy().to_string(),
+        domain,
+        layouts: layouts.to_vec(),
+        entries,
+        skipped,
+    })
+}
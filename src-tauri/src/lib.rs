@@ -2,6 +2,7 @@ mod commands;
 mod core;
 mod models;
 
+use commands::operations::Operations;
 use core::storage::Vault;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -10,6 +11,7 @@ pub fn run() {
 
     tauri::Builder::default()
         .manage(vault)
+        .manage(Operations::default())
         .setup(|app| {
             app.handle().plugin(
                 tauri_plugin_log::Builder::default()
@@ -26,14 +28,51 @@ pub fn run() {
             app.handle().plugin(tauri_plugin_dialog::init())?;
             app.handle().plugin(tauri_plugin_shell::init())?;
             app.handle().plugin(tauri_plugin_fs::init())?;
+            commands::notifications::start_expiry_watcher(app.handle().clone());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::keys::list_keys,
+            commands::keys::search_keys,
             commands::keys::delete_key,
             commands::keys::generate_key,
+            commands::keys::generate_key_with_profile,
             commands::keys::import_key,
+            commands::keys::import_key_file,
+            commands::keys::import_keys_from_text,
+            commands::keys::import_gnupg_home,
             commands::keys::export_key,
+            commands::keys::export_keys,
+            commands::keys::export_vault,
+            commands::keys::add_subkey,
+            commands::keys::revoke_subkey,
+            commands::keys::change_passphrase,
+            commands::keys::certify_key,
+            commands::keys::set_owner_trust,
+            commands::keys::get_key_details,
+            commands::keys::get_fingerprint_aids,
+            commands::keys::verify_fingerprint_uri,
+            commands::keys::toggle_favorite,
+            commands::keys::add_key_tag,
+            commands::keys::remove_key_tag,
+            commands::keys::set_key_notes,
+            commands::keys::audit_keyring,
+            commands::keys::extend_key_expiry,
+            commands::keys::export_paper_backup,
+            commands::keys::export_key_sheet,
+            commands::keys::import_paper_backup,
+            commands::keys::export_key_shares,
+            commands::keys::recover_key_from_shares,
+            commands::keys::export_wkd,
+            commands::keys::autocrypt_header,
+            commands::keys::import_autocrypt_headers,
+            commands::keys::create_autocrypt_setup_message,
+            commands::keys::import_autocrypt_setup_message,
+            commands::keyserver::keyserver_search,
+            commands::keyserver::keyserver_fetch,
+            commands::keyserver::keyserver_upload,
+            commands::notifications::check_expiring_keys,
+            commands::operations::cancel_operation,
             commands::crypto::encrypt_file_cmd,
             commands::crypto::decrypt_file_cmd,
             commands::settings::get_db_path,
@@ -42,6 +81,8 @@ pub fn run() {
             commands::settings::restore_db,
             commands::settings::is_first_run,
             commands::settings::complete_onboarding,
+            commands::settings::get_keyserver_config,
+            commands::settings::set_keyserver_config,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
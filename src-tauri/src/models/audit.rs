@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSeverity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditFindingKind {
+    WeakRsaKey,
+    DsaKey,
+    Sha1Binding,
+    NoEncryptionSubkey,
+    Expiring,
+    Expired,
+    PolicyViolation,
+    UnprotectedSecretKey,
+    DuplicateUserId,
+}
+
+/// One problem found by `audit_keyring`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditFinding {
+    pub fingerprint: String,               // Cert the finding belongs to
+    pub user_id: Option<String>,           // Primary user ID, for display
+    pub component: Option<String>,         // Affected subkey fingerprint or user ID, if narrower than the cert
+    pub kind: AuditFindingKind,
+    pub severity: AuditSeverity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditReport {
+    pub checked_at: String,
+    pub keys_checked: usize,
+    pub findings: Vec<AuditFinding>, // Most severe first
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// Full breakdown of a stored cert, used when debugging interop problems.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyDetails {
+    pub fingerprint: String,
+    pub valid_under_policy: bool, // Whether StandardPolicy accepts the cert at all
+    pub policy_error: Option<String>, // Why it does not
+    pub keys: Vec<KeyComponentDetails>, // Primary key first
+    pub user_ids: Vec<UserIdDetails>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyComponentDetails {
+    pub fingerprint: String,
+    pub key_id: String,
+    pub is_primary: bool,
+    pub algorithm: String,
+    pub bits: Option<usize>,
+    pub curve: Option<String>,
+    pub capabilities: Vec<String>, // "certify", "sign", "encrypt_transport", "encrypt_storage", "authenticate"
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub revoked: bool,
+    pub valid: bool, // Has a binding signature accepted by StandardPolicy
+    pub policy_error: Option<String>, // Why not; capabilities, expiry and revocation are then unknown
+    pub has_secret: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserIdDetails {
+    pub user_id: String,
+    pub is_primary: bool,
+    pub revoked: bool,
+    pub preferred_ciphers: Vec<String>,
+    pub preferred_hashes: Vec<String>,
+    pub preferred_compression: Vec<String>,
+    pub features: Vec<String>, // "seipdv1", "ocb_aead", "v5_keys", "seipdv2" or "unknown_<bit>"
+    pub certifications: Vec<CertificationDetails>,
+    pub policy_error: Option<String>, // Set if StandardPolicy rejects the user ID or the cert
+}
+
+/// A third-party certification on a user ID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificationDetails {
+    pub issuer: String,                 // Issuer fingerprint, or key ID if that is all the signature carries
+    pub issuer_user_id: Option<String>, // Only known if the issuer is in the vault
+    pub created_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub trust_depth: Option<u8>,
+    pub trust_amount: Option<u8>,
+    pub exportable: bool,
+    pub verified: Option<bool>, // None if the issuer is not in the vault
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// A fingerprint in the forms people compare it in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FingerprintAids {
+    pub fingerprint: String,
+    pub spaced: String,     // Hex in groups of four
+    pub words: Vec<String>, // PGP word list
+    pub randomart: String,  // Multi-line, for a monospaced font
+    pub uri: String,        // openpgp4fpr:...
+    pub qr_svg: String,     // QR code of the URI
+}
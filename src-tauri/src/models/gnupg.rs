@@ -0,0 +1,23 @@
+use super::ImportReport;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GnupgImportReport {
+    pub keys: ImportReport,
+    pub files: Vec<String>, // Keyring and export files the keys were read from
+    pub ownertrust_applied: usize,
+    pub ownertrust_skipped: usize, // Entries for keys that are not in the vault
+    pub warnings: Vec<String>,
+}
+
+/// Files written by a whole-vault export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultExport {
+    pub public_keyring: String,
+    pub public_keys: usize,
+    pub secret_keyring: Option<String>, // None unless secret keys were requested
+    pub secret_keys: usize,
+    pub ownertrust_file: String,
+    pub ownertrust_entries: usize,
+    pub skipped: Vec<String>, // Fingerprints of keys that could not be read
+}
@@ -10,28 +10,164 @@ pub struct KeyMetadata {
     pub is_private: bool,
     pub is_favorite: bool,
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub verified_at: Option<String>, // When the fingerprint was last confirmed out of band
+    #[serde(default)]
+    pub protection: Vec<SecretKeyProtection>,
+    #[serde(default)]
+    pub owner_trust: OwnerTrust,
+    #[serde(default)]
+    pub validity: Vec<UserIdValidity>, // Computed on listing, per user ID
 }
 
+/// Protection state of a single secret key packet (primary key or subkey).
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum KeyType {
-    Rsa2048,
-    Rsa4096,
-    Ed25519,
-    Curve25519,
+pub struct SecretKeyProtection {
+    pub fingerprint: String,
+    pub is_primary: bool,
+    pub encrypted: bool,
+    pub s2k: Option<String>,    // e.g. "Iterated+Salted (SHA256, 65011712 bytes)"
+    pub cipher: Option<String>, // Symmetric algorithm protecting the key material
+}
+
+/// Public key algorithm family of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAlgorithm {
+    Rsa,
+    RsaSignOnly,
+    RsaEncryptOnly,
+    Dsa,
+    #[serde(rename = "elgamal")]
+    ElGamal,
+    #[serde(rename = "eddsa")]
+    EdDsa,
+    Ecdsa,
+    Ecdh,
+    Unknown,
+}
+
+/// Algorithm and size of a key's primary key, derived from the cert.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "KeyTypeRepr")]
+pub struct KeyType {
+    pub algorithm: KeyAlgorithm,
+    pub bits: Option<usize>,
+    pub curve: Option<String>, // e.g. "Ed25519", "NIST P-256"
+}
+
+// Metadata written before the algorithm model existed stores the key type as
+// a plain string such as "rsa4096".
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyTypeRepr {
+    Legacy(String),
+    Full {
+        algorithm: KeyAlgorithm,
+        bits: Option<usize>,
+        curve: Option<String>,
+    },
+}
+
+impl From<KeyTypeRepr> for KeyType {
+    fn from(repr: KeyTypeRepr) -> Self {
+        match repr {
+            KeyTypeRepr::Full { algorithm, bits, curve } => KeyType { algorithm, bits, curve },
+            KeyTypeRepr::Legacy(s) => match s.as_str() {
+                "rsa2048" => KeyType { algorithm: KeyAlgorithm::Rsa, bits: Some(2048), curve: None },
+                "rsa4096" => KeyType { algorithm: KeyAlgorithm::Rsa, bits: Some(4096), curve: None },
+                "ed25519" => KeyType {
+                    algorithm: KeyAlgorithm::EdDsa,
+                    bits: Some(256),
+                    curve: Some("Ed25519".to_string()),
+                },
+                "curve25519" => KeyType {
+                    algorithm: KeyAlgorithm::Ecdh,
+                    bits: Some(256),
+                    curve: Some("Curve25519".to_string()),
+                },
+                _ => KeyType { algorithm: KeyAlgorithm::Unknown, bits: None, curve: None },
+            },
+        }
+    }
 }
 
 impl std::fmt::Display for KeyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bits = self.bits.map(|b| b.to_string()).unwrap_or_else(|| "?".to_string());
+        let curve = self.curve.as_deref().unwrap_or("unknown curve");
+        match self.algorithm {
+            KeyAlgorithm::Rsa => write!(f, "RSA {}", bits),
+            KeyAlgorithm::RsaSignOnly => write!(f, "RSA {} (sign only)", bits),
+            KeyAlgorithm::RsaEncryptOnly => write!(f, "RSA {} (encrypt only)", bits),
+            KeyAlgorithm::Dsa => write!(f, "DSA {}", bits),
+            KeyAlgorithm::ElGamal => write!(f, "ElGamal {}", bits),
+            KeyAlgorithm::EdDsa => write!(f, "{}", curve),
+            KeyAlgorithm::Ecdsa => write!(f, "ECDSA {}", curve),
+            KeyAlgorithm::Ecdh => write!(f, "ECDH {}", curve),
+            KeyAlgorithm::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// How much the user trusts a key's owner to certify other keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OwnerTrust {
+    Never,
+    #[default]
+    Unknown,
+    Marginal,
+    Full,
+    Ultimate, // Trust root, normally one of our own keys
+}
+
+impl std::fmt::Display for OwnerTrust {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            KeyType::Rsa2048 => write!(f, "RSA 2048"),
-            KeyType::Rsa4096 => write!(f, "RSA 4096"),
-            KeyType::Ed25519 => write!(f, "Ed25519"),
-            KeyType::Curve25519 => write!(f, "Curve25519"),
+            OwnerTrust::Never => write!(f, "never"),
+            OwnerTrust::Unknown => write!(f, "unknown"),
+            OwnerTrust::Marginal => write!(f, "marginal"),
+            OwnerTrust::Full => write!(f, "full"),
+            OwnerTrust::Ultimate => write!(f, "ultimate"),
         }
     }
 }
 
+impl std::str::FromStr for OwnerTrust {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(OwnerTrust::Never),
+            "unknown" => Ok(OwnerTrust::Unknown),
+            "marginal" => Ok(OwnerTrust::Marginal),
+            "full" => Ok(OwnerTrust::Full),
+            "ultimate" => Ok(OwnerTrust::Ultimate),
+            other => Err(format!("Unknown owner trust level: {}", other)),
+        }
+    }
+}
+
+/// Computed validity of a user ID, i.e. how sure we are that it belongs to the key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Validity {
+    #[default]
+    Unknown,
+    Marginal,
+    Full,
+    Ultimate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserIdValidity {
+    pub user_id: String,
+    pub validity: Validity,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserId {
     pub name: String,
@@ -39,6 +175,71 @@ pub struct UserId {
     pub comment: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    New,
+    Updated,
+    Unchanged,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportEntry {
+    pub fingerprint: Option<String>, // None if the cert could not be parsed at all
+    pub user_id: Option<String>,
+    pub status: ImportStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub entries: Vec<ImportEntry>,
+    pub new: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+}
+
+impl ImportReport {
+    pub fn push(&mut self, entry: ImportEntry) {
+        match entry.status {
+            ImportStatus::New => self.new += 1,
+            ImportStatus::Updated => self.updated += 1,
+            ImportStatus::Unchanged => self.unchanged += 1,
+            ImportStatus::Failed => self.failed += 1,
+        }
+        self.entries.push(entry);
+    }
+
+    pub fn extend(&mut self, other: ImportReport) {
+        for entry in other.entries {
+            self.push(entry);
+        }
+    }
+}
+
+/// Everything needed to generate a key beyond the passphrase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyGenProfile {
+    pub user_ids: Vec<UserId>, // The first one becomes the primary user ID
+    pub key_type: String,
+    pub expiry_timestamp: Option<i64>, // Unix timestamp in seconds, for the primary key
+    #[serde(default = "default_certify_only_primary")]
+    pub certify_only_primary: bool, // If false the primary key can also sign
+    pub subkeys: Vec<SubkeyProfile>,
+}
+
+fn default_certify_only_primary() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubkeyProfile {
+    pub capability: String, // "sign" | "encrypt" | "encrypt_transport" | "encrypt_storage" | "authenticate"
+    pub expiry_timestamp: Option<i64>, // Defaults to the primary key's expiry
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptionResult {
     pub output_path: String, // Renamed from output_file to match usage
@@ -63,4 +264,44 @@ pub struct SignatureInfo {
     pub fingerprint: String,
     pub created_at: String,
     pub valid: bool,
+    #[serde(default)]
+    pub validity: Validity, // Best validity of the signer's user IDs
+}
+
+/// A key that expires within the configured warning window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiringKey {
+    pub fingerprint: String,
+    pub user_id: UserId,
+    pub expires_at: String,
+    pub days_left: i64, // Negative once the key has expired
+    pub is_private: bool, // Only private keys can be extended
+}
+
+/// Payload of the `keys-expiring` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiryNotice {
+    pub window_days: u32,
+    pub keys: Vec<ExpiringKey>,
+}
+
+/// An Autocrypt Setup Message and the code protecting it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutocryptSetupMessage {
+    pub setup_code: String, // Nine blocks of four digits, shown to the user only
+    pub message: String,    // The HTML attachment holding the armored payload
+}
+
+/// Result of a public key export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyExport {
+    pub exported: usize,
+    pub unminimized: Vec<String>, // Keys exported as stored because the policy rejects them
+}
+
+/// A printable key information sheet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeySheetExport {
+    pub qr_content: String, // "public_key", or "fingerprint" when the key is too large
+    pub html: String,       // Always returned for previews
 }
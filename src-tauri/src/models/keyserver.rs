@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// A key as listed by a keyserver search, before it is fetched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyserverKey {
+    pub key_handle: String, // Fingerprint, or a key ID on servers that only list those
+    pub algorithm: Option<String>,
+    pub bits: Option<usize>,
+    pub created_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub revoked: bool,
+    pub user_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyserverAddressStatus {
+    pub address: String,
+    pub status: String, // VKS states: "unpublished", "pending", "published", "revoked"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyserverUpload {
+    pub fingerprint: String,
+    pub addresses: Vec<KeyserverAddressStatus>, // Only reported by VKS servers
+}
@@ -1,7 +1,21 @@
 // Data structures for the application
 
+pub mod audit;
+pub mod details;
+pub mod fingerprint;
+pub mod gnupg;
 pub mod key;
+pub mod keyserver;
 pub mod result;
+pub mod search;
+pub mod wkd;
 
+pub use audit::*;
+pub use details::*;
+pub use fingerprint::*;
+pub use gnupg::*;
 pub use key::*;
+pub use keyserver::*;
 pub use result::*;
+pub use search::*;
+pub use wkd::*;
@@ -24,3 +24,23 @@ impl<T> OperationResult<T> {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationStage {
+    Started,
+    Progress,
+    Finished,
+    Failed,
+    Cancelled,
+}
+
+/// Payload of the `operation-progress` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationProgress {
+    pub operation_id: String,
+    pub kind: String, // e.g. "generate_key", "encrypt_file"
+    pub stage: OperationStage,
+    pub percent: Option<u8>,
+    pub message: String,
+}
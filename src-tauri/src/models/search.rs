@@ -0,0 +1,50 @@
+use super::KeyMetadata;
+use serde::{Deserialize, Serialize};
+
+/// Expiry state a key must be in to match a search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryState {
+    Expired,
+    Expiring, // Not expired yet, but within `expiring_within_days`
+    Valid,    // Not expired, including keys without an expiry date
+    NeverExpires,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySortField {
+    #[default]
+    Name,
+    Email,
+    CreatedAt,
+    ExpiresAt,
+    Fingerprint,
+}
+
+/// Filters for `search_keys`. Every filter is optional and all given filters
+/// must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeySearchQuery {
+    pub text: Option<String>, // Matches name, email, fingerprint suffix or key ID
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub fingerprint: Option<String>, // Suffix of the primary key fingerprint
+    pub key_id: Option<String>,      // Key ID or fingerprint of the primary key or any subkey
+    pub tag: Option<String>,
+    pub favorite: Option<bool>,
+    pub private: Option<bool>,
+    pub expiry: Option<ExpiryState>,
+    pub expiring_within_days: Option<u32>, // Defaults to 30
+    pub sort_by: KeySortField,
+    pub descending: bool,
+    pub offset: u32,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeySearchResult {
+    pub keys: Vec<KeyMetadata>,
+    pub total: usize, // Number of matches before pagination
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Web Key Directory layout. The direct layout is served from the mail
+/// domain itself, the advanced one from the `openpgpkey.` subdomain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WkdLayout {
+    Direct,
+    Advanced,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WkdEntry {
+    pub address: String,
+    pub fingerprints: Vec<String>, // Several certs for one address share a file
+    pub path: String,              // Relative to the output directory
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WkdReport {
+    pub output_dir: String,
+    pub domain: String,
+    pub layouts: Vec<WkdLayout>,
+    pub entries: Vec<WkdEntry>,
+    pub skipped: Vec<String>, // Fingerprints without a valid user ID in the domain
+}
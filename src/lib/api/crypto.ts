@@ -8,6 +8,7 @@ export async function encryptFile(params: {
   signWith?: string;
   passphrase?: string;
   armor: boolean;
+  operationId?: string;
 }): Promise<OperationResult<EncryptionResult>> {
   return invoke('encrypt_file', {
     inputPath: params.inputPath,
@@ -16,6 +17,7 @@ export async function encryptFile(params: {
     signWith: params.signWith,
     passphrase: params.passphrase,
     armor: params.armor,
+    operationId: params.operationId,
   });
 }
 
@@ -23,10 +25,12 @@ export async function decryptFile(params: {
   inputPath: string;
   outputPath: string;
   passphrase?: string;
+  operationId?: string;
 }): Promise<OperationResult<DecryptionResult>> {
   return invoke('decrypt_file', {
     inputPath: params.inputPath,
     outputPath: params.outputPath,
     passphrase: params.passphrase,
+    operationId: params.operationId,
   });
 }
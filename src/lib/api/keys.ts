@@ -1,5 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { KeyMetadata, OperationResult } from "$lib/types/key";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type { AuditReport, AutocryptSetupMessage, ExpiringKey, ExpiryNotice, FingerprintAids, GnupgImportReport, ImportReport, KeyDetails, KeyExport, KeyGenProfile, KeySheetExport, KeyMetadata, KeySearchQuery, KeySearchResult, KeyserverKey, KeyserverUpload, OperationResult, OwnerTrust, SubkeyCapability, VaultExport, WkdReport } from "$lib/types/key";
 
 export async function listKeys(): Promise<OperationResult<KeyMetadata[]>> {
   try {
@@ -9,6 +10,14 @@ export async function listKeys(): Promise<OperationResult<KeyMetadata[]>> {
   }
 }
 
+export async function searchKeys(query: KeySearchQuery): Promise<OperationResult<KeySearchResult>> {
+  try {
+    return await invoke("search_keys", { query });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
 export async function deleteKey(fingerprint: string): Promise<OperationResult<boolean>> {
   try {
     return await invoke("delete_key", { fingerprint });
@@ -24,6 +33,7 @@ export async function generateKey(params: {
   comment?: string;
   keyType: string;
   expiryTimestamp?: number;
+  operationId?: string;
 }): Promise<OperationResult<KeyMetadata>> {
   try {
     return await invoke("generate_key", params);
@@ -32,7 +42,19 @@ export async function generateKey(params: {
   }
 }
 
-export async function importKey(keyText: string): Promise<OperationResult<KeyMetadata>> {
+export async function generateKeyWithProfile(
+  profile: KeyGenProfile,
+  passphrase: string,
+  operationId?: string
+): Promise<OperationResult<KeyMetadata>> {
+  try {
+    return await invoke("generate_key_with_profile", { profile, passphrase, operationId });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function importKey(keyText: string): Promise<OperationResult<ImportReport>> {
   try {
     return await invoke("import_key", { keyText });
   } catch (e) {
@@ -40,6 +62,19 @@ export async function importKey(keyText: string): Promise<OperationResult<KeyMet
   }
 }
 
+export async function importGnupgHome(params: {
+  homeDir?: string; // Defaults to $GNUPGHOME or ~/.gnupg; "" skips the home
+  keyFiles: string[]; // e.g. the output of `gpg --export-secret-keys`
+  ownertrustFile?: string; // The output of `gpg --export-ownertrust`
+  operationId?: string;
+}): Promise<OperationResult<GnupgImportReport>> {
+  try {
+    return await invoke("import_gnupg_home", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
 export async function exportKey(fingerprint: string, exportPrivate: boolean): Promise<OperationResult<string>> {
   try {
     return await invoke("export_key", { fingerprint: fingerprint, exportPrivate: exportPrivate });
@@ -47,3 +82,340 @@ export async function exportKey(fingerprint: string, exportPrivate: boolean): Pr
     return { success: false, error: String(e) };
   }
 }
+
+export async function addSubkey(params: {
+  fingerprint: string;
+  capability: SubkeyCapability;
+  keyType: string;
+  passphrase: string;
+  expiryTimestamp?: number;
+  operationId?: string;
+}): Promise<OperationResult<string>> {
+  try {
+    return await invoke("add_subkey", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function revokeSubkey(params: {
+  fingerprint: string;
+  subkeyFingerprint: string;
+  passphrase: string;
+  reason: "superseded" | "compromised" | "retired" | "unspecified";
+  message?: string;
+}): Promise<OperationResult<boolean>> {
+  try {
+    return await invoke("revoke_subkey", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function changePassphrase(params: {
+  fingerprint: string;
+  oldPassphrase: string;
+  newPassphrase: string;
+  confirmRemoveProtection: boolean;
+  operationId?: string;
+}): Promise<OperationResult<boolean>> {
+  try {
+    return await invoke("change_passphrase", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function importKeyFile(path: string): Promise<OperationResult<ImportReport>> {
+  try {
+    return await invoke("import_key_file", { path });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function importKeysFromText(text: string): Promise<OperationResult<ImportReport>> {
+  try {
+    return await invoke("import_keys_from_text", { text });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function exportKeys(params: {
+  fingerprints: string[];
+  outputPath: string;
+  armor: boolean;
+  minimal: boolean;
+  allPublic: boolean;
+}): Promise<OperationResult<KeyExport>> {
+  try {
+    return await invoke("export_keys", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function exportVault(params: {
+  outputDir: string;
+  armor: boolean;
+  includeSecret: boolean;
+}): Promise<OperationResult<VaultExport>> {
+  try {
+    return await invoke("export_vault", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function certifyKey(params: {
+  targetFingerprint: string;
+  signerFingerprint: string;
+  passphrase: string;
+  userIds: string[];
+  trustDepth: number;
+  trustAmount?: number;
+  expiryTimestamp?: number;
+  local: boolean;
+}): Promise<OperationResult<boolean>> {
+  try {
+    return await invoke("certify_key", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function setOwnerTrust(fingerprint: string, trust: OwnerTrust): Promise<OperationResult<boolean>> {
+  try {
+    return await invoke("set_owner_trust", { fingerprint, trust });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function getKeyDetails(fingerprint: string): Promise<OperationResult<KeyDetails>> {
+  try {
+    return await invoke("get_key_details", { fingerprint });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function getFingerprintAids(fingerprint: string): Promise<OperationResult<FingerprintAids>> {
+  try {
+    return await invoke("get_fingerprint_aids", { fingerprint });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function verifyFingerprintUri(params: {
+  uri: string;
+  expectedFingerprint?: string;
+}): Promise<OperationResult<KeyMetadata>> {
+  try {
+    return await invoke("verify_fingerprint_uri", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function toggleFavorite(fingerprint: string): Promise<OperationResult<KeyMetadata>> {
+  try {
+    return await invoke("toggle_favorite", { fingerprint });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function addKeyTag(fingerprint: string, tag: string): Promise<OperationResult<KeyMetadata>> {
+  try {
+    return await invoke("add_key_tag", { fingerprint, tag });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function removeKeyTag(fingerprint: string, tag: string): Promise<OperationResult<KeyMetadata>> {
+  try {
+    return await invoke("remove_key_tag", { fingerprint, tag });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function setKeyNotes(fingerprint: string, notes: string | null): Promise<OperationResult<KeyMetadata>> {
+  try {
+    return await invoke("set_key_notes", { fingerprint, notes });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function auditKeyring(expiringWithinDays?: number): Promise<OperationResult<AuditReport>> {
+  try {
+    return await invoke("audit_keyring", { expiringWithinDays });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function extendKeyExpiry(params: {
+  fingerprint: string;
+  passphrase: string;
+  expiryTimestamp?: number;
+}): Promise<OperationResult<KeyMetadata>> {
+  try {
+    return await invoke("extend_key_expiry", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function checkExpiringKeys(windowDays?: number): Promise<OperationResult<ExpiringKey[]>> {
+  try {
+    return await invoke("check_expiring_keys", { windowDays });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function onKeysExpiring(handler: (notice: ExpiryNotice) => void): Promise<UnlistenFn> {
+  return await listen<ExpiryNotice>("keys-expiring", (event) => handler(event.payload));
+}
+
+export async function exportPaperBackup(params: {
+  fingerprint: string;
+  format: "text" | "pdf";
+  outputPath?: string;
+}): Promise<OperationResult<string>> {
+  try {
+    return await invoke("export_paper_backup", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function exportKeySheet(params: {
+  fingerprint: string;
+  format: "html" | "pdf";
+  qrContent?: "public_key" | "fingerprint";
+  outputPath?: string;
+}): Promise<OperationResult<KeySheetExport>> {
+  try {
+    return await invoke("export_key_sheet", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function importPaperBackup(paperText: string, publicKey?: string): Promise<OperationResult<KeyMetadata>> {
+  try {
+    return await invoke("import_paper_backup", { paperText, publicKey });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function exportKeyShares(params: {
+  fingerprint: string;
+  threshold: number;
+  shares: number;
+  format: "text" | "armor";
+}): Promise<OperationResult<string[]>> {
+  try {
+    return await invoke("export_key_shares", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function recoverKeyFromShares(shares: string[]): Promise<OperationResult<string>> {
+  try {
+    return await invoke("recover_key_from_shares", { shares });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function exportWkd(params: {
+  fingerprints: string[];
+  outputDir: string;
+  domain: string;
+  layout: "direct" | "advanced" | "both";
+  minimal: boolean;
+}): Promise<OperationResult<WkdReport>> {
+  try {
+    return await invoke("export_wkd", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function autocryptHeader(params: {
+  fingerprint: string;
+  addr?: string;
+  preferEncryptMutual: boolean;
+  gossip: boolean;
+}): Promise<OperationResult<string>> {
+  try {
+    return await invoke("autocrypt_header", params);
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function importAutocryptHeaders(text: string): Promise<OperationResult<ImportReport>> {
+  try {
+    return await invoke("import_autocrypt_headers", { text });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function createAutocryptSetupMessage(
+  fingerprint: string,
+  passphrase: string
+): Promise<OperationResult<AutocryptSetupMessage>> {
+  try {
+    return await invoke("create_autocrypt_setup_message", { fingerprint, passphrase });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function importAutocryptSetupMessage(
+  message: string,
+  setupCode: string,
+  passphrase: string
+): Promise<OperationResult<KeyMetadata>> {
+  try {
+    return await invoke("import_autocrypt_setup_message", { message, setupCode, passphrase });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function keyserverSearch(query: string): Promise<OperationResult<KeyserverKey[]>> {
+  try {
+    return await invoke("keyserver_search", { query });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function keyserverFetch(query: string): Promise<OperationResult<ImportReport>> {
+  try {
+    return await invoke("keyserver_fetch", { query });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function keyserverUpload(fingerprint: string): Promise<OperationResult<KeyserverUpload>> {
+  try {
+    return await invoke("keyserver_upload", { fingerprint });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
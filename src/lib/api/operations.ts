@@ -0,0 +1,21 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type { OperationProgress, OperationResult } from "$lib/types/key";
+
+export function newOperationId(): string {
+  return `op-${Date.now()}-${Math.random().toString(36).slice(2, 8)}`;
+}
+
+export async function onOperationProgress(
+  handler: (progress: OperationProgress) => void
+): Promise<UnlistenFn> {
+  return await listen<OperationProgress>("operation-progress", (event) => handler(event.payload));
+}
+
+export async function cancelOperation(operationId: string): Promise<OperationResult<boolean>> {
+  try {
+    return await invoke("cancel_operation", { operationId });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { OperationResult } from "$lib/types/key";
+import type { KeyserverConfig, OperationResult } from "$lib/types/key";
 
 export async function getDbPath(): Promise<OperationResult<string>> {
   try {
@@ -48,3 +48,19 @@ export async function completeOnboarding(): Promise<OperationResult<boolean>> {
     return { success: false, error: String(e) };
   }
 }
+
+export async function getKeyserverConfig(): Promise<OperationResult<KeyserverConfig>> {
+  try {
+    return await invoke("get_keyserver_config");
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
+
+export async function setKeyserverConfig(keyserver: KeyserverConfig): Promise<OperationResult<boolean>> {
+  try {
+    return await invoke("set_keyserver_config", { keyserver });
+  } catch (e) {
+    return { success: false, error: String(e) };
+  }
+}
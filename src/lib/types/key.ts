@@ -1,12 +1,77 @@
 export interface KeyMetadata {
   fingerprint: string;
-  key_type: 'Rsa2048' | 'Rsa4096' | 'Ed25519' | 'Curve25519';
+  key_type: KeyType;
   user_id: UserId;
   created_at: string;
   expires_at?: string;
   is_private: boolean;
   is_favorite: boolean;
   tags: string[];
+  notes?: string;
+  verified_at?: string; // When the fingerprint was last confirmed out of band
+  protection: SecretKeyProtection[];
+  owner_trust: OwnerTrust;
+  validity: UserIdValidity[];
+}
+
+export type ExpiryState = 'expired' | 'expiring' | 'valid' | 'never_expires';
+
+export type KeySortField = 'name' | 'email' | 'created_at' | 'expires_at' | 'fingerprint';
+
+export interface KeySearchQuery {
+  text?: string;
+  name?: string;
+  email?: string;
+  fingerprint?: string;
+  key_id?: string;
+  tag?: string;
+  favorite?: boolean;
+  private?: boolean;
+  expiry?: ExpiryState;
+  expiring_within_days?: number;
+  sort_by?: KeySortField;
+  descending?: boolean;
+  offset?: number;
+  limit?: number;
+}
+
+export interface KeySearchResult {
+  keys: KeyMetadata[];
+  total: number;
+}
+
+export type OwnerTrust = 'never' | 'unknown' | 'marginal' | 'full' | 'ultimate';
+
+export type Validity = 'unknown' | 'marginal' | 'full' | 'ultimate';
+
+export interface UserIdValidity {
+  user_id: string;
+  validity: Validity;
+}
+
+export interface SecretKeyProtection {
+  fingerprint: string;
+  is_primary: boolean;
+  encrypted: boolean;
+  s2k?: string;
+  cipher?: string;
+}
+
+export type KeyAlgorithm =
+  | 'rsa'
+  | 'rsa_sign_only'
+  | 'rsa_encrypt_only'
+  | 'dsa'
+  | 'elgamal'
+  | 'eddsa'
+  | 'ecdsa'
+  | 'ecdh'
+  | 'unknown';
+
+export interface KeyType {
+  algorithm: KeyAlgorithm;
+  bits?: number;
+  curve?: string;
 }
 
 export interface UserId {
@@ -15,12 +80,98 @@ export interface UserId {
   comment?: string;
 }
 
+export type ImportStatus = 'new' | 'updated' | 'unchanged' | 'failed';
+
+export interface ImportEntry {
+  fingerprint?: string;
+  user_id?: string;
+  status: ImportStatus;
+  error?: string;
+}
+
+export interface ImportReport {
+  entries: ImportEntry[];
+  new: number;
+  updated: number;
+  unchanged: number;
+  failed: number;
+}
+
+export interface GnupgImportReport {
+  keys: ImportReport;
+  files: string[]; // Keyring and export files the keys were read from
+  ownertrust_applied: number;
+  ownertrust_skipped: number; // Entries for keys that are not in the vault
+  warnings: string[];
+}
+
+// Files written by a whole-vault export
+export interface KeyExport {
+  exported: number;
+  unminimized: string[]; // Keys exported as stored because the policy rejects them
+}
+
+export interface VaultExport {
+  public_keyring: string;
+  public_keys: number;
+  secret_keyring: string | null; // null unless secret keys were requested
+  secret_keys: number;
+  ownertrust_file: string;
+  ownertrust_entries: number;
+  skipped: string[]; // Fingerprints of keys that could not be read
+}
+
+export interface FingerprintAids {
+  fingerprint: string;
+  spaced: string; // Hex in groups of four
+  words: string[]; // PGP word list
+  randomart: string; // Multi-line, for a monospaced font
+  uri: string; // openpgp4fpr:...
+  qr_svg: string; // QR code of the URI
+}
+
+export interface KeySheetExport {
+  qr_content: "public_key" | "fingerprint"; // "fingerprint" when the key is too large
+  html: string; // Always returned for previews
+}
+
+export type SubkeyCapability =
+  | 'sign'
+  | 'encrypt'
+  | 'encrypt_transport'
+  | 'encrypt_storage'
+  | 'authenticate';
+
+export interface SubkeyProfile {
+  capability: SubkeyCapability;
+  expiry_timestamp?: number;
+}
+
+export interface KeyGenProfile {
+  user_ids: UserId[];
+  key_type: string;
+  expiry_timestamp?: number;
+  certify_only_primary: boolean;
+  subkeys: SubkeyProfile[];
+}
+
 export interface OperationResult<T> {
   success: boolean;
   data?: T;
   error?: string;
 }
 
+export type OperationStage = "started" | "progress" | "finished" | "failed" | "cancelled";
+
+// Payload of the "operation-progress" event
+export interface OperationProgress {
+  operation_id: string;
+  kind: string;
+  stage: OperationStage;
+  percent?: number;
+  message: string;
+}
+
 export interface EncryptionResult {
   output_file: string;
   size: number;
@@ -40,4 +191,144 @@ export interface SignatureInfo {
   fingerprint: string;
   created_at: string;
   valid: boolean;
+  validity: Validity;
+}
+
+export interface KeyDetails {
+  fingerprint: string;
+  valid_under_policy: boolean;
+  policy_error?: string;
+  keys: KeyComponentDetails[];
+  user_ids: UserIdDetails[];
+}
+
+export interface KeyComponentDetails {
+  fingerprint: string;
+  key_id: string;
+  is_primary: boolean;
+  algorithm: string;
+  bits?: number;
+  curve?: string;
+  capabilities: string[];
+  created_at: string;
+  expires_at?: string;
+  revoked: boolean;
+  valid: boolean;
+  policy_error?: string; // capabilities, expiry and revocation are unknown when set
+  has_secret: boolean;
+}
+
+export interface UserIdDetails {
+  user_id: string;
+  is_primary: boolean;
+  revoked: boolean;
+  preferred_ciphers: string[];
+  preferred_hashes: string[];
+  preferred_compression: string[];
+  features: string[];
+  certifications: CertificationDetails[];
+  policy_error?: string;
+}
+
+export interface CertificationDetails {
+  issuer: string;
+  issuer_user_id?: string;
+  created_at?: string;
+  expires_at?: string;
+  trust_depth?: number;
+  trust_amount?: number;
+  exportable: boolean;
+  verified?: boolean;
+}
+
+export type AuditSeverity = 'info' | 'low' | 'medium' | 'high' | 'critical';
+
+export type AuditFindingKind =
+  | 'weak_rsa_key'
+  | 'dsa_key'
+  | 'sha1_binding'
+  | 'no_encryption_subkey'
+  | 'expiring'
+  | 'expired'
+  | 'policy_violation'
+  | 'unprotected_secret_key'
+  | 'duplicate_user_id';
+
+export interface AuditFinding {
+  fingerprint: string;
+  user_id?: string;
+  component?: string;
+  kind: AuditFindingKind;
+  severity: AuditSeverity;
+  message: string;
+}
+
+export interface AuditReport {
+  checked_at: string;
+  keys_checked: number;
+  findings: AuditFinding[];
+}
+
+export interface ExpiringKey {
+  fingerprint: string;
+  user_id: UserId;
+  expires_at: string;
+  days_left: number;
+  is_private: boolean;
+}
+
+// Payload of the "keys-expiring" event
+export interface ExpiryNotice {
+  window_days: number;
+  keys: ExpiringKey[];
+}
+
+export type WkdLayout = 'direct' | 'advanced';
+
+export interface WkdEntry {
+  address: string;
+  fingerprints: string[];
+  path: string;
+}
+
+export interface WkdReport {
+  output_dir: string;
+  domain: string;
+  layouts: WkdLayout[];
+  entries: WkdEntry[];
+  skipped: string[];
+}
+
+export interface AutocryptSetupMessage {
+  setup_code: string; // Nine blocks of four digits
+  message: string; // HTML attachment holding the armored payload
+}
+
+export type KeyserverProtocol = 'hkp' | 'vks';
+
+// Keyserver access is off unless enabled in the settings
+export interface KeyserverConfig {
+  enabled: boolean;
+  url: string | null; // hkp://, hkps://, http:// or https://
+  protocol: KeyserverProtocol;
+}
+
+export interface KeyserverKey {
+  key_handle: string; // Fingerprint, or a key ID on servers that only list those
+  algorithm: string | null;
+  bits: number | null;
+  created_at: string | null;
+  expires_at: string | null;
+  revoked: boolean;
+  user_ids: string[];
+}
+
+export interface KeyserverAddressStatus {
+  address: string;
+  status: string; // "unpublished" | "pending" | "published" | "revoked"
+}
+
+export interface KeyserverUpload {
+  fingerprint: string;
+  addresses: KeyserverAddressStatus[]; // Only reported by VKS servers
 }